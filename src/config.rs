@@ -1,10 +1,137 @@
+use crate::severity::Severity;
+use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 
+/// Ordered key/value attributes carried by a [`LogEntry`].
+pub type Fields = IndexMap<String, FieldValue>;
+
 #[derive(Debug, Serialize, Deserialize, Default)]
 pub struct LogEntry {
     pub timestamp: Option<String>,
     pub host: Option<String>,
     pub service: Option<String>,
-    pub level: Option<String>,
+    pub level: Option<Severity>,
     pub message: Option<String>,
+    /// The parser's original timestamp text, kept by the normalizer.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw_timestamp: Option<String>,
+    /// Request details for HTTP access logs.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub http: Option<HttpRequest>,
+    /// Parser-specific attributes that don't map onto a core field.
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub fields: Fields,
+}
+
+impl LogEntry {
+    /// Insert (or replace) an attribute in [`LogEntry::fields`].
+    pub fn set_field(&mut self, key: impl Into<String>, value: impl Into<FieldValue>) {
+        self.fields.insert(key.into(), value.into());
+    }
+}
+
+/// Typed attribute value. Serialized untagged, so JSON output keeps native
+/// types (`"a"`, `1`, `1.5`, `true`, `[..]`, `{..}`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum FieldValue {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+    List(Vec<FieldValue>),
+    Map(Fields),
+}
+
+impl From<&str> for FieldValue {
+    fn from(v: &str) -> Self {
+        FieldValue::String(v.to_string())
+    }
+}
+
+impl From<String> for FieldValue {
+    fn from(v: String) -> Self {
+        FieldValue::String(v)
+    }
+}
+
+impl From<i64> for FieldValue {
+    fn from(v: i64) -> Self {
+        FieldValue::Int(v)
+    }
+}
+
+impl From<u32> for FieldValue {
+    fn from(v: u32) -> Self {
+        FieldValue::Int(v.into())
+    }
+}
+
+impl From<f64> for FieldValue {
+    fn from(v: f64) -> Self {
+        FieldValue::Float(v)
+    }
+}
+
+impl From<bool> for FieldValue {
+    fn from(v: bool) -> Self {
+        FieldValue::Bool(v)
+    }
+}
+
+impl From<Vec<FieldValue>> for FieldValue {
+    fn from(v: Vec<FieldValue>) -> Self {
+        FieldValue::List(v)
+    }
+}
+
+impl From<Fields> for FieldValue {
+    fn from(v: Fields) -> Self {
+        FieldValue::Map(v)
+    }
+}
+
+/// Typed fields of an HTTP access-log line.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct HttpRequest {
+    pub method: Option<String>,
+    pub path: Option<String>,
+    /// e.g. `HTTP/1.1`
+    pub protocol: Option<String>,
+    pub status: Option<u16>,
+    /// Response body size; CLF `-` is reported as `0`.
+    pub bytes: Option<u64>,
+    pub referrer: Option<String>,
+    pub user_agent: Option<String>,
+    pub remote_user: Option<String>,
+    /// Request processing time in milliseconds.
+    pub duration_ms: Option<f64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fields_round_trip_in_order() {
+        let mut nested = Fields::new();
+        nested.insert("id".into(), 7i64.into());
+        let mut entry = LogEntry::default();
+        entry.set_field("zeta", "z");
+        entry.set_field("alpha", 1.5);
+        entry.set_field("ok", true);
+        entry.set_field("user", nested);
+
+        let json = serde_json::to_string(&entry).unwrap();
+        assert!(json.ends_with(r#""fields":{"zeta":"z","alpha":1.5,"ok":true,"user":{"id":7}}}"#));
+        assert!(
+            !serde_json::to_string(&LogEntry::default())
+                .unwrap()
+                .contains("fields")
+        );
+
+        let back: LogEntry = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.fields, entry.fields);
+    }
 }
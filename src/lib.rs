@@ -0,0 +1,26 @@
+//! LogNorm: high-performance log parsing and normalization.
+//!
+//! The crate can be embedded directly instead of shelling out to the
+//! `lognorm` binary:
+//!
+//! ```no_run
+//! let input = std::fs::read_to_string("/var/log/nginx/access.log")?;
+//! let entries = lognorm::normalize(lognorm::parse("nginx", &input)?);
+//!
+//! let mut writer = lognorm::create_writer("out.jsonl")?;
+//! writer.write_batch(&entries)?;
+//! writer.finish()?;
+//! # Ok::<(), anyhow::Error>(())
+//! ```
+
+pub mod config;
+pub mod normalizer;
+pub mod output;
+pub mod parsers;
+pub mod severity;
+
+pub use config::{FieldValue, Fields, LogEntry};
+pub use normalizer::{NormalizeOptions, normalize, normalize_with};
+pub use output::{Writer, create_writer};
+pub use parsers::parse;
+pub use severity::Severity;
@@ -1,32 +1,135 @@
-mod config;
-mod normalizer;
-mod output;
-mod parsers;
-
-use anyhow::Result;
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Utc};
 use clap::Parser;
-use crossbeam::channel::unbounded;
+use crossbeam::channel::Sender;
+use lognorm::normalizer::{AssumedTz, TimestampPrecision};
+use lognorm::parsers::container::ContainerParser;
+use lognorm::parsers::grok::{Grok, GrokParser};
+use lognorm::parsers::json::{JsonKeys, JsonParser};
+use lognorm::parsers::log_format::LogFormatParser;
+use lognorm::parsers::multiline::DEFAULT_MAX_LINES;
+use lognorm::parsers::python_format::PythonFormatParser;
+use lognorm::parsers::regex_parser::RegexParser;
+use lognorm::parsers::{
+    self, AUTO_PRESET, LogParser, Multiline, MultilineParser, detect::DEFAULT_SAMPLE_LINES,
+};
+use lognorm::{LogEntry, NormalizeOptions, Severity, create_writer, normalize_with};
 use memchr::memchr_iter;
 use memmap2::Mmap;
+#[cfg(feature = "parallel")]
 use rayon::prelude::*;
 use std::fs::File;
+use std::sync::Arc;
 use std::time::Instant;
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 struct Args {
-    #[arg(short, long)]
-    preset: String,
+    /// Parser preset, or `auto` to detect the format of each file
+    #[arg(
+        short,
+        long,
+        required_unless_present_any = ["list_presets", "python_format", "nginx_format", "apache_format", "pattern", "regex"]
+    )]
+    preset: Option<String>,
+
+    /// Python `logging` format string (e.g. `%(asctime)s [%(levelname)s]
+    /// %(name)s: %(message)s`), registered as the `python_format` preset and
+    /// used when no --preset is given
+    #[arg(long, value_name = "FORMAT")]
+    python_format: Option<String>,
+
+    /// Nginx `log_format` string (e.g. `$remote_addr [$time_local] "$request"
+    /// $status $request_time`), registered as the `nginx_format` preset and
+    /// used when no --preset is given
+    #[arg(long, value_name = "FORMAT")]
+    nginx_format: Option<String>,
+
+    /// Apache `LogFormat` string (e.g. `%h %l %u %t "%r" %>s %b %D`),
+    /// registered as the `apache_format` preset and used when no --preset is
+    /// given
+    #[arg(long, value_name = "FORMAT")]
+    apache_format: Option<String>,
+
+    /// Grok expression (e.g. `%{IPORHOST:host} %{LOGLEVEL:level}
+    /// %{GREEDYDATA:message}`), registered as the `grok` preset and used when
+    /// no --preset is given; repeat to try several in order
+    #[arg(long, value_name = "GROK")]
+    pattern: Vec<String>,
+
+    /// File of extra `NAME regex` grok pattern definitions; repeatable
+    #[arg(long, value_name = "FILE")]
+    pattern_file: Vec<String>,
+
+    /// Regex whose named groups (`timestamp`, `level`, `message`, `host`,
+    /// `service`, anything else as a field) fill each entry, registered as the
+    /// `regex` preset and used when no --preset is given; repeat to try
+    /// several in order
+    #[arg(long, value_name = "REGEX")]
+    regex: Vec<String>,
+
+    /// Keys the `json` preset reads a core field from, as FIELD=KEY[,KEY...]
+    /// (fields: timestamp, level, message, host, service); repeatable
+    #[arg(long, value_name = "FIELD=KEYS")]
+    json_key: Vec<String>,
+
+    /// Preset that parses the messages of `docker` and `cri` container logs
+    /// (e.g. `json`); messages it rejects are kept as plain text
+    #[arg(long, value_name = "PRESET")]
+    payload_preset: Option<String>,
+
+    /// List the registered parser presets and exit
+    #[arg(long)]
+    list_presets: bool,
 
     #[arg(short, long, default_value = "stdout")]
     output: String,
 
-    #[arg(value_name = "FILE")]
-    file: String,
+    #[arg(value_name = "FILE", required_unless_present = "list_presets")]
+    files: Vec<String>,
 
     #[arg(long, default_value = "1000000")]
     batch_size: usize,
 
+    /// Number of lines sampled per file by `--preset auto`
+    #[arg(long, default_value_t = DEFAULT_SAMPLE_LINES)]
+    sample_lines: usize,
+
+    /// Fractional seconds in normalized timestamps: seconds, millis, micros or nanos
+    #[arg(long, default_value = "millis")]
+    ts_precision: TimestampPrecision,
+
+    /// Zone for timestamps without one: UTC, local, or an offset like +05:30
+    #[arg(long, default_value = "UTC")]
+    assume_tz: AssumedTz,
+
+    /// Year of year-less (syslog/journal) timestamps; inferred from each
+    /// file's modification time when omitted
+    #[arg(long)]
+    assume_year: Option<i32>,
+
+    /// Drop entries below this severity (trace, debug, info, notice, warn,
+    /// error, critical, alert, emergency); entries without a level are dropped too
+    #[arg(long)]
+    min_level: Option<Severity>,
+
+    /// Regex for lines that start a new record; any other line is appended
+    /// to the previous entry
+    #[arg(long, value_name = "REGEX")]
+    multiline_start: Option<String>,
+
+    /// Regex for lines that continue the previous record (e.g. `^\s+at `)
+    #[arg(long, value_name = "REGEX")]
+    multiline_continuation: Option<String>,
+
+    /// Fold Java, Go, Rust and Python stack traces into the entry before them
+    #[arg(long)]
+    stack_traces: bool,
+
+    /// Maximum number of lines grouped into one multiline record
+    #[arg(long, default_value_t = DEFAULT_MAX_LINES)]
+    multiline_max_lines: usize,
+
     #[arg(long)]
     benchmark: bool,
 }
@@ -34,65 +137,271 @@ struct Args {
 fn main() -> Result<()> {
     let args = Args::parse();
 
-    let start_time = Instant::now();
-    let file_metadata = std::fs::metadata(&args.file)?;
-    let file_size = file_metadata.len();
+    if let Some(format) = &args.python_format {
+        parsers::register(
+            PythonFormatParser::new(format)
+                .map_err(|e| anyhow!("invalid --python-format: {}", e))?,
+        );
+    }
 
-    // mmap the file
-    let file = File::open(&args.file)?;
-    let mmap = unsafe { Mmap::map(&file)? };
+    if let Some(format) = &args.nginx_format {
+        parsers::register(
+            LogFormatParser::nginx(format).map_err(|e| anyhow!("invalid --nginx-format: {}", e))?,
+        );
+    }
 
-    // find newline offsets
-    let line_positions: Vec<usize> = memchr_iter(b'\n', &mmap).collect();
-    let total_lines = line_positions.len();
-
-    // slice into batches
-    let batches: Vec<&[u8]> = line_positions
-        .chunks(args.batch_size)
-        .map(|chunk| {
-            let start = chunk.first().copied().unwrap_or(0);
-            let end = chunk.last().copied().unwrap_or(mmap.len() - 1);
-            &mmap[start..=end]
-        })
-        .collect();
+    if let Some(format) = &args.apache_format {
+        parsers::register(
+            LogFormatParser::apache(format)
+                .map_err(|e| anyhow!("invalid --apache-format: {}", e))?,
+        );
+    }
+
+    if !args.pattern.is_empty() {
+        let mut grok = Grok::default();
+        for path in &args.pattern_file {
+            grok.load_file(path)
+                .map_err(|e| anyhow!("invalid --pattern-file: {:#}", e))?;
+        }
+        parsers::register(
+            GrokParser::new(&grok, &args.pattern)
+                .map_err(|e| anyhow!("invalid --pattern: {}", e))?,
+        );
+    }
+
+    if !args.regex.is_empty() {
+        parsers::register(
+            RegexParser::new(&args.regex).map_err(|e| anyhow!("invalid --regex: {}", e))?,
+        );
+    }
+
+    if !args.json_key.is_empty() {
+        let mut keys = JsonKeys::default();
+        for spec in &args.json_key {
+            keys.set(spec)
+                .map_err(|e| anyhow!("invalid --json-key: {}", e))?;
+        }
+        parsers::register(JsonParser::new(keys));
+    }
+
+    if let Some(name) = &args.payload_preset {
+        let payload = parsers::lookup(name).ok_or_else(|| {
+            anyhow!(
+                "Unknown --payload-preset: {}. Use --list-presets to see available presets",
+                name
+            )
+        })?;
+        parsers::register(ContainerParser::docker().with_payload(payload.clone()));
+        parsers::register(ContainerParser::cri().with_payload(payload));
+    }
+
+    if args.list_presets {
+        print_presets();
+        return Ok(());
+    }
+    let preset = match &args.preset {
+        Some(preset) => preset.as_str(),
+        None if args.python_format.is_some() => "python_format",
+        None if args.nginx_format.is_some() => "nginx_format",
+        None if args.apache_format.is_some() => "apache_format",
+        None if !args.pattern.is_empty() => "grok",
+        None if !args.regex.is_empty() => "regex",
+        None => unreachable!("clap enforces --preset or a custom format"),
+    };
+    let fixed_parser = if preset == AUTO_PRESET {
+        None
+    } else {
+        Some(parsers::lookup(preset).ok_or_else(|| {
+            anyhow!(
+                "Unknown parser: {}. Use --list-presets to see available presets",
+                preset
+            )
+        })?)
+    };
+
+    let multiline = multiline_rules(&args)?;
+
+    let normalize_opts = NormalizeOptions {
+        precision: args.ts_precision,
+        assume_tz: args.assume_tz,
+        assume_year: args.assume_year,
+        reference_time: None,
+    };
+
+    let start_time = Instant::now();
 
     // channel for sending parsed batches to writer
-    let (tx, rx) = crossbeam::channel::unbounded::<Vec<config::LogEntry>>();
+    let (tx, rx) = crossbeam::channel::unbounded::<Vec<LogEntry>>();
 
     // spawn writer thread
     let output_arg = args.output.clone();
     let writer_handle = std::thread::spawn(move || {
-        let mut writer = output::create_writer(&output_arg).unwrap();
+        let mut writer = create_writer(&output_arg).unwrap();
         for batch in rx {
             writer.write_batch(&batch).unwrap();
         }
         writer.finish().unwrap();
     });
 
-    // parallel parse batches
-    let total_entries: usize = batches
-        .par_iter()
+    let mut total_size = 0u64;
+    let mut total_lines = 0usize;
+    let mut total_entries = 0usize;
+    for path in &args.files {
+        // mmap the file
+        let file = File::open(path)?;
+        let metadata = file.metadata()?;
+        total_size += metadata.len();
+        let mmap = unsafe { Mmap::map(&file)? };
+
+        // year-less timestamps are resolved relative to the file's mtime
+        let file_opts = NormalizeOptions {
+            reference_time: metadata.modified().ok().map(DateTime::<Utc>::from),
+            ..normalize_opts.clone()
+        };
+
+        let parser = match &fixed_parser {
+            Some(parser) => Arc::clone(parser),
+            None => {
+                let detection = parsers::detect(&mmap, args.sample_lines)
+                    .ok_or_else(|| anyhow!("{}: could not detect log format", path))?;
+                eprintln!(
+                    "{}: detected '{}' (confidence {:.1}%, {} lines sampled)",
+                    path,
+                    detection.parser.name(),
+                    detection.confidence * 100.0,
+                    detection.sampled
+                );
+                detection.parser
+            }
+        };
+        let parser: Arc<dyn LogParser> = match &multiline {
+            Some(rules) => Arc::new(MultilineParser::new(parser, rules.clone())),
+            None => parser,
+        };
+
+        let (lines, entries) = parse_file(
+            &mmap,
+            parser.as_ref(),
+            args.batch_size,
+            &file_opts,
+            args.min_level,
+            &tx,
+        );
+        total_lines += lines;
+        total_entries += entries;
+    }
+
+    // close channel so writer thread can finish
+    drop(tx);
+    writer_handle.join().unwrap();
+
+    if args.benchmark {
+        print_benchmark_results(total_size, total_lines, total_entries, start_time.elapsed());
+    }
+
+    Ok(())
+}
+
+/// Parse one mmapped file in record-aligned batches, sending each normalized
+/// batch to the writer. Returns `(lines, entries)`.
+fn parse_file(
+    data: &[u8],
+    parser: &dyn LogParser,
+    batch_size: usize,
+    normalize_opts: &NormalizeOptions,
+    min_level: Option<Severity>,
+    tx: &Sender<Vec<LogEntry>>,
+) -> (usize, usize) {
+    // find newline offsets
+    let line_positions: Vec<usize> = memchr_iter(b'\n', data).collect();
+    let has_trailing_line = line_positions
+        .last()
+        .map_or(!data.is_empty(), |&nl| nl + 1 < data.len());
+    let total_lines = line_positions.len() + has_trailing_line as usize;
+
+    // slice into batches of about `batch_size` lines, covering the whole
+    // file and ending on a record boundary so no record is split
+    let mut batches: Vec<&[u8]> = Vec::new();
+    let mut start = 0;
+    for chunk in line_positions.chunks(batch_size.max(1)) {
+        // search from the last cut, so the parser only sees the current batch
+        let pos = (chunk[chunk.len() - 1] + 1).max(start);
+        let end = start + parser.record_start(&data[start..], pos - start);
+        if end > start {
+            batches.push(&data[start..end]);
+            start = end;
+        }
+    }
+    if start < data.len() {
+        batches.push(&data[start..]);
+    }
+
+    // parse batches (in parallel when the `parallel` feature is enabled)
+    #[cfg(feature = "parallel")]
+    let batch_iter = batches.par_iter();
+    #[cfg(not(feature = "parallel"))]
+    let batch_iter = batches.iter();
+
+    let total_entries: usize = batch_iter
         .map(|batch| {
-            let s = unsafe { std::str::from_utf8_unchecked(batch) };
-            let parsed = match parsers::parse(&args.preset, s) {
-                Ok(parsed) => normalizer::normalize(parsed),
+            let mut parsed = match parser.parse_bytes(batch) {
+                Ok(parsed) => normalize_with(parsed, normalize_opts),
                 Err(_) => Vec::new(),
             };
+            if let Some(min) = min_level {
+                parsed.retain(|e| e.level.is_some_and(|level| level >= min));
+            }
             let len = parsed.len();
             tx.send(parsed).unwrap();
             len
         })
         .sum();
 
-    // close channel so writer thread can finish
-    drop(tx);
-    writer_handle.join().unwrap();
+    (total_lines, total_entries)
+}
 
-    if args.benchmark {
-        print_benchmark_results(file_size, total_lines, total_entries, start_time.elapsed());
+/// Multiline grouping requested on the command line, if any.
+fn multiline_rules(args: &Args) -> Result<Option<Multiline>> {
+    if args.multiline_start.is_none() && args.multiline_continuation.is_none() && !args.stack_traces
+    {
+        return Ok(None);
+    }
+    let mut rules = if args.stack_traces {
+        Multiline::stack_traces()
+    } else {
+        Multiline::new()
+    };
+    if let Some(pattern) = &args.multiline_start {
+        rules = rules
+            .start(pattern)
+            .map_err(|e| anyhow!("invalid --multiline-start pattern: {}", e))?;
+    }
+    if let Some(pattern) = &args.multiline_continuation {
+        rules = rules
+            .continuation(pattern)
+            .map_err(|e| anyhow!("invalid --multiline-continuation pattern: {}", e))?;
     }
+    Ok(Some(rules.max_lines(args.multiline_max_lines)))
+}
 
-    Ok(())
+fn print_presets() {
+    for parser in parsers::registered() {
+        let aliases = parser.aliases();
+        if aliases.is_empty() {
+            println!("{:<12} {}", parser.name(), parser.description());
+        } else {
+            println!(
+                "{:<12} {} (aliases: {})",
+                parser.name(),
+                parser.description(),
+                aliases.join(", ")
+            );
+        }
+    }
+    println!(
+        "{:<12} Detect the format of each input file from a sample of its lines",
+        AUTO_PRESET
+    );
 }
 
 fn print_benchmark_results(
@@ -1,14 +1,443 @@
 use crate::config::LogEntry;
+use chrono::{
+    DateTime, Datelike, FixedOffset, Local, NaiveDate, NaiveDateTime, SecondsFormat, TimeDelta,
+    TimeZone, Utc,
+};
+use std::str::FromStr;
 
+/// Fractional-second digits in normalized timestamps.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TimestampPrecision {
+    Seconds,
+    #[default]
+    Millis,
+    Micros,
+    Nanos,
+}
+
+impl TimestampPrecision {
+    fn seconds_format(self) -> SecondsFormat {
+        match self {
+            TimestampPrecision::Seconds => SecondsFormat::Secs,
+            TimestampPrecision::Millis => SecondsFormat::Millis,
+            TimestampPrecision::Micros => SecondsFormat::Micros,
+            TimestampPrecision::Nanos => SecondsFormat::Nanos,
+        }
+    }
+}
+
+impl FromStr for TimestampPrecision {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "s" | "secs" | "seconds" => Ok(TimestampPrecision::Seconds),
+            "ms" | "millis" => Ok(TimestampPrecision::Millis),
+            "us" | "micros" => Ok(TimestampPrecision::Micros),
+            "ns" | "nanos" => Ok(TimestampPrecision::Nanos),
+            _ => Err(format!(
+                "unknown timestamp precision '{}' (expected seconds, millis, micros or nanos)",
+                s
+            )),
+        }
+    }
+}
+
+/// Zone assumed for timestamps that don't carry one.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AssumedTz {
+    #[default]
+    Utc,
+    Local,
+    Fixed(FixedOffset),
+}
+
+impl AssumedTz {
+    fn resolve(self, naive: NaiveDateTime) -> Option<DateTime<Utc>> {
+        match self {
+            AssumedTz::Utc => Some(naive.and_utc()),
+            AssumedTz::Local => Local
+                .from_local_datetime(&naive)
+                .earliest()
+                .map(|ts| ts.with_timezone(&Utc)),
+            AssumedTz::Fixed(offset) => offset
+                .from_local_datetime(&naive)
+                .single()
+                .map(|ts| ts.with_timezone(&Utc)),
+        }
+    }
+}
+
+impl FromStr for AssumedTz {
+    type Err = String;
+
+    /// `UTC`, `local`, or an offset such as `+05:30` / `-0800`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "UTC" | "utc" | "Z" | "z" => return Ok(AssumedTz::Utc),
+            "local" | "Local" => return Ok(AssumedTz::Local),
+            _ => {}
+        }
+        let invalid = || {
+            format!(
+                "invalid timezone '{}' (expected UTC, local or an offset like +05:30)",
+                s
+            )
+        };
+        let (sign, rest) = match s.as_bytes() {
+            [b'+', rest @ ..] => (1, rest),
+            [b'-', rest @ ..] => (-1, rest),
+            _ => return Err(invalid()),
+        };
+        // ±HH:MM or ±HHMM
+        let digits = match rest {
+            [h1, h2, b':', m1, m2] | [h1, h2, m1, m2] => [*h1, *h2, *m1, *m2],
+            _ => return Err(invalid()),
+        };
+        if !digits.iter().all(u8::is_ascii_digit) {
+            return Err(invalid());
+        }
+        let [h1, h2, m1, m2] = digits.map(|d| i32::from(d - b'0'));
+        let (hours, minutes) = (h1 * 10 + h2, m1 * 10 + m2);
+        if hours > 23 || minutes >= 60 {
+            return Err(format!("timezone offset out of range: '{}'", s));
+        }
+        FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+            .map(AssumedTz::Fixed)
+            .ok_or_else(|| format!("timezone offset out of range: '{}'", s))
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct NormalizeOptions {
+    pub precision: TimestampPrecision,
+    /// Zone for timestamps without one (syslog, Python logging, naive ISO).
+    pub assume_tz: AssumedTz,
+    /// Year of the entries, for formats without a year (BSD syslog,
+    /// journalctl short output). Every year-less timestamp is placed in it;
+    /// `reference_time` is then ignored (`Feb 29` outside a leap year goes to
+    /// the latest leap year before it).
+    pub assume_year: Option<i32>,
+    /// Time the log was last written, typically the file mtime. Year-less
+    /// timestamps are placed in the year that puts them at or before this
+    /// instant (defaults to now).
+    pub reference_time: Option<DateTime<Utc>>,
+}
+
+/// Resolved per-call view of [`NormalizeOptions`].
+struct Resolver {
+    tz: AssumedTz,
+    reference: DateTime<Utc>,
+}
+
+impl Resolver {
+    fn new(opts: &NormalizeOptions) -> Self {
+        // an explicit year holds every entry: anchor at its last second
+        let reference = opts
+            .assume_year
+            .and_then(|year| Utc.with_ymd_and_hms(year, 12, 31, 23, 59, 59).single())
+            .or(opts.reference_time)
+            .unwrap_or_else(Utc::now);
+        Self {
+            tz: opts.assume_tz,
+            reference,
+        }
+    }
+
+    /// Place `Mmm dd hh:mm:ss[.fff]` in the reference year, or the year
+    /// before if that would be more than a day past the reference. This
+    /// keeps a file spanning Dec 31 -> Jan 1 in order without needing to see
+    /// it sequentially. `Feb 29` goes back to the latest leap year.
+    fn yearless(&self, raw: &str) -> Option<DateTime<Utc>> {
+        let year = self.reference.year();
+        let latest = self.reference + TimeDelta::days(1);
+        // leap years can be up to 8 apart (2096 -> 2104)
+        (0..=8).find_map(|back| {
+            let naive = NaiveDateTime::parse_from_str(
+                &format!("{} {}", year - back, raw),
+                "%Y %b %e %H:%M:%S%.f",
+            )
+            .ok()?;
+            self.tz.resolve(naive).filter(|ts| *ts <= latest)
+        })
+    }
+}
+
+/// Normalize with default options (see [`normalize_with`]).
 pub fn normalize(entries: Vec<LogEntry>) -> Vec<LogEntry> {
+    normalize_with(entries, &NormalizeOptions::default())
+}
+
+/// Rewrite each entry's `timestamp` as an RFC 3339 UTC string, keeping the
+/// parser's original text in `raw_timestamp`. Timestamps that can't be
+/// parsed are moved to `raw_timestamp` and `timestamp` is cleared, so a set
+/// `timestamp` is always normalized.
+pub fn normalize_with(entries: Vec<LogEntry>, opts: &NormalizeOptions) -> Vec<LogEntry> {
+    let resolver = Resolver::new(opts);
     entries
         .into_iter()
         .map(|mut e| {
-            // Example: normalize timestamps (TODO)
-            if let Some(ts) = e.timestamp.clone() {
-                e.timestamp = Some(ts); // convert to ISO-8601 later
+            if let Some(raw) = e.timestamp.take() {
+                e.timestamp = resolve_timestamp(&raw, &resolver)
+                    .map(|ts| ts.to_rfc3339_opts(opts.precision.seconds_format(), true));
+                e.raw_timestamp = Some(raw);
             }
             e
         })
         .collect()
 }
+
+/// Parse the native timestamp formats produced by the built-in parsers:
+///
+/// - RFC 3339 / ISO 8601 (`2025-05-12T06:25:24.123Z`, `...+02:00`)
+/// - nginx/apache CLF (`12/May/2025:06:25:24 +0000`)
+/// - Django/Werkzeug development servers (`12/May/2025 06:25:24`)
+/// - Python logging (`2025-08-31 22:50:01,234`), date only (`2025-08-31`)
+/// - Slash-separated dates (`2025/08/31 22:50:01`, nginx error log)
+/// - Unix epoch seconds (`1756680601.234`), millis, micros or nanos
+/// - ctime with a year (`Mon May 12 06:25:24.123456 2025`, Apache error log)
+/// - BSD syslog (`Aug 31 22:50:01`), placed in the most recent matching year
+///
+/// Zone and year assumptions come from `opts` (see [`NormalizeOptions`]).
+pub fn parse_timestamp(raw: &str, opts: &NormalizeOptions) -> Option<DateTime<Utc>> {
+    resolve_timestamp(raw, &Resolver::new(opts))
+}
+
+fn resolve_timestamp(raw: &str, resolver: &Resolver) -> Option<DateTime<Utc>> {
+    let raw = raw.trim();
+    let b = raw.as_bytes();
+    if b.len() < 10 {
+        return None;
+    }
+
+    // 1756680601[.234], or 13/16/19 digits of millis/micros/nanos
+    if b.iter().all(|c| c.is_ascii_digit() || *c == b'.') {
+        return parse_epoch(raw);
+    }
+
+    // 12/May/2025:06:25:24 +0000, or 12/May/2025 06:25:24 (Django, Werkzeug)
+    if b[2] == b'/' {
+        if let Ok(ts) = DateTime::parse_from_str(raw, "%d/%b/%Y:%H:%M:%S %z") {
+            return Some(ts.with_timezone(&Utc));
+        }
+        return NaiveDateTime::parse_from_str(raw, "%d/%b/%Y %H:%M:%S")
+            .ok()
+            .and_then(|ts| resolver.tz.resolve(ts));
+    }
+
+    // Mon May 12 06:25:24[.123456] 2025
+    if b[3] == b' ' && b[4..7].iter().all(u8::is_ascii_alphabetic) {
+        return NaiveDateTime::parse_from_str(raw, "%a %b %e %H:%M:%S%.f %Y")
+            .ok()
+            .and_then(|ts| resolver.tz.resolve(ts));
+    }
+
+    // Aug 31 22:50:01
+    if b[0].is_ascii_alphabetic() {
+        return resolver.yearless(raw);
+    }
+
+    // ISO-like: 2025-08-31[T ]22:50:01[.,]fff[zone], or 2025/08/31 22:50:01
+    if (b[4] == b'-' && b[7] == b'-') || (b[4] == b'/' && b[7] == b'/') {
+        let iso = raw
+            .replacen('/', "-", 2)
+            .replacen(' ', "T", 1)
+            .replacen(',', ".", 1);
+        if b.len() == 10 {
+            return NaiveDate::parse_from_str(&iso, "%Y-%m-%d")
+                .ok()
+                .and_then(|d| d.and_hms_opt(0, 0, 0))
+                .and_then(|ts| resolver.tz.resolve(ts));
+        }
+        if let Ok(ts) = DateTime::parse_from_rfc3339(&iso)
+            .or_else(|_| DateTime::parse_from_str(&iso, "%Y-%m-%dT%H:%M:%S%.f%z"))
+        {
+            return Some(ts.with_timezone(&Utc));
+        }
+        return NaiveDateTime::parse_from_str(&iso, "%Y-%m-%dT%H:%M:%S%.f")
+            .ok()
+            .and_then(|ts| resolver.tz.resolve(ts));
+    }
+
+    None
+}
+
+/// Unix time in seconds (with optional fraction), or as a whole number of
+/// milliseconds, microseconds or nanoseconds, told apart by digit count.
+fn parse_epoch(raw: &str) -> Option<DateTime<Utc>> {
+    let (whole, frac) = raw.split_once('.').unwrap_or((raw, ""));
+    let n: i64 = whole.parse().ok()?;
+    match (whole.len(), frac.is_empty()) {
+        (10, _) => {
+            let nanos = format!("{:0<9}", frac).get(..9)?.parse().ok()?;
+            DateTime::from_timestamp(n, nanos)
+        }
+        (13, true) => DateTime::from_timestamp_millis(n),
+        (16, true) => DateTime::from_timestamp_micros(n),
+        (19, true) => Some(DateTime::from_timestamp_nanos(n)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ts(raw: &str) -> Option<LogEntry> {
+        let entry = LogEntry {
+            timestamp: Some(raw.to_string()),
+            ..Default::default()
+        };
+        normalize(vec![entry]).pop()
+    }
+
+    #[test]
+    fn normalizes_builtin_formats() {
+        let nginx = ts("12/May/2025:06:25:24 +0530").unwrap();
+        assert_eq!(nginx.timestamp.as_deref(), Some("2025-05-12T00:55:24.000Z"));
+        assert_eq!(
+            nginx.raw_timestamp.as_deref(),
+            Some("12/May/2025:06:25:24 +0530")
+        );
+
+        let python = ts("2025-08-31 22:50:01,234").unwrap();
+        assert_eq!(
+            python.timestamp.as_deref(),
+            Some("2025-08-31T22:50:01.234Z")
+        );
+        let iso = ts("2025-10-11T22:14:15.003+02:00").unwrap();
+        assert_eq!(iso.timestamp.as_deref(), Some("2025-10-11T20:14:15.003Z"));
+        let dev = ts("31/Aug/2025 22:50:01").unwrap();
+        assert_eq!(dev.timestamp.as_deref(), Some("2025-08-31T22:50:01.000Z"));
+        let slashed = ts("2025/08/31 22:50:01").unwrap();
+        assert_eq!(
+            slashed.timestamp.as_deref(),
+            Some("2025-08-31T22:50:01.000Z")
+        );
+        let offset = ts("2025-08-31T22:50:01+0200").unwrap();
+        assert_eq!(
+            offset.timestamp.as_deref(),
+            Some("2025-08-31T20:50:01.000Z")
+        );
+        for epoch in ["1756680601.25", "1756680601250", "1756680601250000"] {
+            let e = ts(epoch).unwrap();
+            assert_eq!(e.timestamp.as_deref(), Some("2025-08-31T22:50:01.250Z"));
+        }
+        let ctime = ts("Mon May 12 06:25:24.123456 2025").unwrap();
+        assert_eq!(ctime.timestamp.as_deref(), Some("2025-05-12T06:25:24.123Z"));
+        let date = ts("2025-08-31").unwrap();
+        assert_eq!(date.timestamp.as_deref(), Some("2025-08-31T00:00:00.000Z"));
+
+        let syslog = ts("Aug  1 22:50:01").unwrap();
+        assert!(syslog.timestamp.unwrap().ends_with("-08-01T22:50:01.000Z"));
+
+        let bad = ts("not a timestamp").unwrap();
+        assert_eq!(bad.timestamp, None);
+        assert_eq!(bad.raw_timestamp.as_deref(), Some("not a timestamp"));
+
+        let opts = NormalizeOptions {
+            precision: "seconds".parse().unwrap(),
+            ..Default::default()
+        };
+        let entry = LogEntry {
+            timestamp: Some("2025-08-31 22:50:01,234".into()),
+            ..Default::default()
+        };
+        let secs = normalize_with(vec![entry], &opts).pop().unwrap();
+        assert_eq!(secs.timestamp.as_deref(), Some("2025-08-31T22:50:01Z"));
+    }
+
+    #[test]
+    fn infers_year_across_rollover() {
+        let mut opts = NormalizeOptions {
+            reference_time: Some(Utc.with_ymd_and_hms(2026, 1, 1, 10, 0, 0).unwrap()),
+            ..Default::default()
+        };
+        let at = |raw: &str, opts: &NormalizeOptions| {
+            parse_timestamp(raw, opts).map(|ts| ts.to_rfc3339_opts(SecondsFormat::Secs, true))
+        };
+        assert_eq!(
+            at("Dec 31 23:59:00", &opts).as_deref(),
+            Some("2025-12-31T23:59:00Z")
+        );
+        assert_eq!(
+            at("Jan  1 00:01:00", &opts).as_deref(),
+            Some("2026-01-01T00:01:00Z")
+        );
+
+        opts.assume_year = Some(2020);
+        opts.assume_tz = "+02:00".parse().unwrap();
+        assert_eq!(
+            at("Dec 31 23:59:00", &opts).as_deref(),
+            Some("2020-12-31T21:59:00Z")
+        );
+        assert_eq!(
+            at("Jan  1 00:01:00", &opts).as_deref(),
+            Some("2019-12-31T22:01:00Z")
+        );
+        // later in the year than the reference time's month
+        assert_eq!(
+            at("Nov 15 08:00:00", &opts).as_deref(),
+            Some("2020-11-15T06:00:00Z")
+        );
+        assert_eq!(
+            at("2025-08-31 22:50:01", &opts).as_deref(),
+            Some("2025-08-31T20:50:01Z")
+        );
+        assert!("+5".parse::<AssumedTz>().is_err());
+    }
+
+    #[test]
+    fn assumed_tz_parses_exact_offsets() {
+        let east = |secs| Ok(AssumedTz::Fixed(FixedOffset::east_opt(secs).unwrap()));
+        assert_eq!("+05:30".parse::<AssumedTz>(), east(5 * 3600 + 30 * 60));
+        assert_eq!("-0800".parse::<AssumedTz>(), east(-8 * 3600));
+        assert_eq!("+23:59".parse::<AssumedTz>(), east(23 * 3600 + 59 * 60));
+        for bad in [
+            "+0:530", "+05+30", "+5:030", "05:30", "+05:3", "+05:300", "+0a:30", "+05:60",
+            "+24:00", "+9960",
+        ] {
+            assert!(bad.parse::<AssumedTz>().is_err(), "{bad:?}");
+        }
+    }
+
+    #[test]
+    fn yearless_keeps_fractional_seconds() {
+        let opts = NormalizeOptions {
+            reference_time: Some(Utc.with_ymd_and_hms(2025, 9, 1, 0, 0, 0).unwrap()),
+            ..Default::default()
+        };
+        let ts = parse_timestamp("Aug 31 22:50:01.007", &opts).unwrap();
+        assert_eq!(
+            ts.to_rfc3339_opts(SecondsFormat::Millis, true),
+            "2025-08-31T22:50:01.007Z"
+        );
+    }
+
+    #[test]
+    fn yearless_feb_29_goes_to_a_leap_year() {
+        let at = |reference: DateTime<Utc>| {
+            let opts = NormalizeOptions {
+                reference_time: Some(reference),
+                ..Default::default()
+            };
+            parse_timestamp("Feb 29 12:00:00", &opts).map(|ts| ts.year())
+        };
+        assert_eq!(
+            at(Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap()),
+            Some(2024)
+        );
+        assert_eq!(
+            at(Utc.with_ymd_and_hms(2026, 6, 1, 0, 0, 0).unwrap()),
+            Some(2024)
+        );
+        assert_eq!(
+            at(Utc.with_ymd_and_hms(2028, 1, 15, 0, 0, 0).unwrap()),
+            Some(2024)
+        );
+        assert_eq!(
+            at(Utc.with_ymd_and_hms(2103, 6, 1, 0, 0, 0).unwrap()),
+            Some(2096)
+        );
+    }
+}
@@ -1,15 +1,185 @@
 use crate::config::LogEntry;
-use anyhow::{anyhow, Result};
+use anyhow::{Result, anyhow};
+use std::collections::HashMap;
 use std::fs::File;
-use std::io::{self, BufWriter, Write};
-use std::path::Path;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// Columns every CSV/TSV output starts with.
+const CORE_COLUMNS: [&str; 5] = ["timestamp", "host", "service", "level", "message"];
 
 pub enum Writer {
     Stdout(Box<dyn Write>),
-    JsonFile(BufWriter<File>, bool), // bool tracks if we've written the opening bracket
+    JsonFile(BufWriter<File>, bool), // bool is true until the first entry (and `[`) is written
     JsonlFile(BufWriter<File>),
-    CsvFile(BufWriter<File>, bool), // bool tracks if we've written headers
-    TsvFile(BufWriter<File>, bool),
+    CsvFile(Table),
+    TsvFile(Table),
+}
+
+/// A CSV/TSV output file. Rows are streamed under a header of the columns
+/// known when the first rows are written; nested values are flattened into
+/// dotted column names (`http.status`) and new names are appended. If any
+/// column first appears later, [`finish`](Table::finish) rewrites the file
+/// once with the full header, padding the earlier, shorter rows.
+#[derive(Debug)]
+pub struct Table {
+    writer: BufWriter<File>,
+    path: PathBuf,
+    format: TableFormat,
+    columns: Vec<String>,
+    index: HashMap<String, usize>,
+    /// Number of columns in the header on disk, once it's written.
+    header: Option<usize>,
+    rows: u64,
+    /// `(first row, columns)` for each run of rows with the same width.
+    widths: Vec<(u64, usize)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TableFormat {
+    Csv,
+    Tsv,
+}
+
+impl TableFormat {
+    fn sep(self) -> &'static str {
+        match self {
+            TableFormat::Csv => ",",
+            TableFormat::Tsv => "\t",
+        }
+    }
+
+    fn escape(self, field: &str) -> String {
+        match self {
+            TableFormat::Csv => escape_csv_field(field),
+            TableFormat::Tsv => escape_tsv_field(field),
+        }
+    }
+}
+
+impl Table {
+    fn create(path: &str, format: TableFormat) -> Result<Self> {
+        create_parent_dirs(path)?;
+        Ok(Self {
+            writer: BufWriter::new(File::create(path)?),
+            path: PathBuf::from(path),
+            format,
+            columns: Vec::new(),
+            index: HashMap::new(),
+            header: None,
+            rows: 0,
+            widths: Vec::new(),
+        })
+    }
+
+    /// Write `logs` as rows, registering their columns first so a batch
+    /// never needs padding of its own.
+    fn push(&mut self, logs: &[LogEntry]) -> Result<()> {
+        if logs.is_empty() {
+            return Ok(());
+        }
+        let mut rows = Vec::with_capacity(logs.len());
+        for log in logs {
+            let row: Vec<(usize, String)> = flatten_entry(log)?
+                .into_iter()
+                .map(|(key, value)| {
+                    let next = self.columns.len();
+                    let col = *self.index.entry(key).or_insert_with_key(|key| {
+                        self.columns.push(key.clone());
+                        next
+                    });
+                    (col, value)
+                })
+                .collect();
+            rows.push(row);
+        }
+
+        if self.header.is_none() {
+            write_header(&mut self.writer, &self.columns, self.format)?;
+            self.header = Some(self.columns.len());
+        }
+        if self.widths.last().map(|&(_, n)| n) != Some(self.columns.len()) {
+            self.widths.push((self.rows, self.columns.len()));
+        }
+        let sep = self.format.sep();
+        let mut cells = vec![String::new(); self.columns.len()];
+        for row in rows {
+            cells.iter_mut().for_each(String::clear);
+            for (col, value) in row {
+                cells[col] = self.format.escape(&value);
+            }
+            writeln!(self.writer, "{}", cells.join(sep))?;
+            self.rows += 1;
+        }
+        Ok(())
+    }
+
+    /// Flush the file, first rewriting it under the full header if columns
+    /// were added after it was written. With no rows, write just the core
+    /// columns.
+    pub fn finish(mut self) -> Result<()> {
+        let Some(header) = self.header else {
+            writeln!(self.writer, "{}", CORE_COLUMNS.join(self.format.sep()))?;
+            self.writer.flush()?;
+            return Ok(());
+        };
+        self.writer.flush()?;
+        drop(self.writer);
+        if header == self.columns.len() {
+            return Ok(());
+        }
+
+        let mut tmp = self.path.clone().into_os_string();
+        tmp.push(".tmp");
+        let mut reader = BufReader::new(File::open(&self.path)?);
+        let mut out = BufWriter::new(File::create(&tmp)?);
+        write_header(&mut out, &self.columns, self.format)?;
+
+        let quoted = self.format == TableFormat::Csv;
+        let mut record = Vec::new();
+        read_record(&mut reader, &mut record, quoted)?; // old header
+        let mut widths = self.widths.iter().peekable();
+        let mut width = 0;
+        for row in 0..self.rows {
+            if let Some(&(_, n)) = widths.next_if(|&&(first, _)| first <= row) {
+                width = n;
+            }
+            read_record(&mut reader, &mut record, quoted)?;
+            out.write_all(record.strip_suffix(b"\n").unwrap_or(&record))?;
+            for _ in width..self.columns.len() {
+                out.write_all(self.format.sep().as_bytes())?;
+            }
+            out.write_all(b"\n")?;
+        }
+        out.flush()?;
+        drop(out);
+        std::fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+}
+
+fn write_header(writer: &mut impl Write, columns: &[String], format: TableFormat) -> Result<()> {
+    let header: Vec<String> = columns.iter().map(|n| format.escape(n)).collect();
+    writeln!(writer, "{}", header.join(format.sep()))?;
+    Ok(())
+}
+
+/// Read one row into `buf`, including its `\n`. CSV cells are quoted when
+/// they hold a newline, so with `quoted` a line with an odd number of `"`
+/// continues on the next one.
+fn read_record(reader: &mut impl BufRead, buf: &mut Vec<u8>, quoted: bool) -> Result<()> {
+    buf.clear();
+    let mut quotes = 0;
+    loop {
+        let start = buf.len();
+        if reader.read_until(b'\n', buf)? == 0 {
+            return Ok(());
+        }
+        quotes += buf[start..].iter().filter(|&&b| b == b'"').count();
+        if !quoted || quotes % 2 == 0 {
+            return Ok(());
+        }
+    }
 }
 
 impl Writer {
@@ -21,17 +191,10 @@ impl Writer {
                 }
             }
             Writer::JsonFile(writer, is_first) => {
-                if *is_first {
-                    write!(writer, "[")?;
+                for log in logs {
+                    // `[` before the first entry, `,` before every other one
+                    write!(writer, "{}", if *is_first { "[" } else { "," })?;
                     *is_first = false;
-                } else {
-                    write!(writer, ",")?;
-                }
-
-                for (i, log) in logs.iter().enumerate() {
-                    if i > 0 {
-                        write!(writer, ",")?;
-                    }
                     let serialized = serde_json::to_string_pretty(log)?;
                     write!(writer, "\n{}", serialized)?;
                 }
@@ -42,41 +205,8 @@ impl Writer {
                     writeln!(writer, "{}", serialized)?;
                 }
             }
-            Writer::CsvFile(writer, headers_written) => {
-                if !*headers_written {
-                    writeln!(writer, "timestamp,host,service,level,message")?;
-                    *headers_written = true;
-                }
-
-                for log in logs {
-                    writeln!(
-                        writer,
-                        "{},{},{},{},{}",
-                        escape_csv_field(&log.timestamp.as_deref().unwrap_or("")),
-                        escape_csv_field(&log.host.as_deref().unwrap_or("")),
-                        escape_csv_field(&log.service.as_deref().unwrap_or("")),
-                        escape_csv_field(&log.level.as_deref().unwrap_or("")),
-                        escape_csv_field(&log.message.as_deref().unwrap_or(""))
-                    )?;
-                }
-            }
-            Writer::TsvFile(writer, headers_written) => {
-                if !*headers_written {
-                    writeln!(writer, "timestamp\thost\tservice\tlevel\tmessage")?;
-                    *headers_written = true;
-                }
-
-                for log in logs {
-                    writeln!(
-                        writer,
-                        "{}\t{}\t{}\t{}\t{}",
-                        escape_tsv_field(&log.timestamp.as_deref().unwrap_or("")),
-                        escape_tsv_field(&log.host.as_deref().unwrap_or("")),
-                        escape_tsv_field(&log.service.as_deref().unwrap_or("")),
-                        escape_tsv_field(&log.level.as_deref().unwrap_or("")),
-                        escape_tsv_field(&log.message.as_deref().unwrap_or(""))
-                    )?;
-                }
+            Writer::CsvFile(table) | Writer::TsvFile(table) => {
+                table.push(logs)?;
             }
         }
         Ok(())
@@ -84,13 +214,17 @@ impl Writer {
 
     pub fn finish(mut self) -> Result<()> {
         match self {
-            Writer::JsonFile(ref mut writer, _) => {
+            Writer::CsvFile(table) | Writer::TsvFile(table) => {
+                table.finish()?;
+            }
+            Writer::JsonFile(ref mut writer, is_first) => {
+                if is_first {
+                    write!(writer, "[")?;
+                }
                 writeln!(writer, "\n]")?;
                 writer.flush()?;
             }
-            Writer::JsonlFile(ref mut writer)
-            | Writer::CsvFile(ref mut writer, _)
-            | Writer::TsvFile(ref mut writer, _) => {
+            Writer::JsonlFile(ref mut writer) => {
                 writer.flush()?;
             }
             Writer::Stdout(ref mut writer) => {
@@ -118,16 +252,10 @@ pub fn create_writer(output_arg: &str) -> Result<Writer> {
             Ok(Writer::JsonlFile(writer))
         }
         path if path.ends_with(".csv") => {
-            create_parent_dirs(path)?;
-            let file = File::create(path)?;
-            let writer = BufWriter::new(file);
-            Ok(Writer::CsvFile(writer, false))
+            Ok(Writer::CsvFile(Table::create(path, TableFormat::Csv)?))
         }
         path if path.ends_with(".tsv") => {
-            create_parent_dirs(path)?;
-            let file = File::create(path)?;
-            let writer = BufWriter::new(file);
-            Ok(Writer::TsvFile(writer, false))
+            Ok(Writer::TsvFile(Table::create(path, TableFormat::Tsv)?))
         }
         path => {
             // Default to JSON file if it looks like a path
@@ -153,6 +281,31 @@ fn create_parent_dirs(file_path: &str) -> Result<()> {
     Ok(())
 }
 
+/// Serialize `log` and flatten it into `(dotted.key, cell)` pairs.
+fn flatten_entry(log: &LogEntry) -> Result<Vec<(String, String)>> {
+    fn walk(prefix: String, value: serde_json::Value, out: &mut Vec<(String, String)>) {
+        match value {
+            serde_json::Value::Object(map) => {
+                for (key, value) in map {
+                    let key = if prefix.is_empty() {
+                        key
+                    } else {
+                        format!("{}.{}", prefix, key)
+                    };
+                    walk(key, value, out);
+                }
+            }
+            serde_json::Value::Null => out.push((prefix, String::new())),
+            serde_json::Value::String(s) => out.push((prefix, s)),
+            other => out.push((prefix, other.to_string())),
+        }
+    }
+
+    let mut out = Vec::new();
+    walk(String::new(), serde_json::to_value(log)?, &mut out);
+    Ok(out)
+}
+
 fn escape_csv_field(field: &str) -> String {
     if field.contains(',') || field.contains('"') || field.contains('\n') {
         format!("\"{}\"", field.replace('"', "\"\""))
@@ -162,10 +315,7 @@ fn escape_csv_field(field: &str) -> String {
 }
 
 fn escape_tsv_field(field: &str) -> String {
-    field
-        .replace('\t', " ")
-        .replace('\n', " ")
-        .replace('\r', " ")
+    field.replace(['\t', '\n', '\r'], " ")
 }
 
 // Legacy function for backward compatibility
@@ -174,3 +324,118 @@ pub fn write(output_arg: &str, logs: &[LogEntry]) -> Result<()> {
     writer.write_batch(logs)?;
     writer.finish()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{FieldValue, Fields};
+
+    #[test]
+    fn json_array_skips_empty_batches() {
+        let path = std::env::temp_dir().join(format!("lognorm-{}.json", std::process::id()));
+        let path = path.to_str().unwrap();
+        let entry = || LogEntry {
+            message: Some("kept".into()),
+            ..Default::default()
+        };
+        let mut writer = create_writer(path).unwrap();
+        for batch in [vec![], vec![entry()], vec![], vec![entry()]] {
+            writer.write_batch(&batch).unwrap();
+        }
+        writer.finish().unwrap();
+        let parsed: Vec<LogEntry> =
+            serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+        assert_eq!(parsed.len(), 2);
+
+        // every batch filtered out: still an (empty) array
+        let mut writer = create_writer(path).unwrap();
+        writer.write_batch(&[]).unwrap();
+        writer.finish().unwrap();
+        let parsed: Vec<LogEntry> =
+            serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+        std::fs::remove_file(path).unwrap();
+        assert!(parsed.is_empty());
+    }
+
+    #[test]
+    fn flattens_fields_into_dotted_columns() {
+        let mut user = Fields::new();
+        user.insert("id".into(), 7i64.into());
+        let mut entry = LogEntry {
+            message: Some("hi".into()),
+            ..Default::default()
+        };
+        entry.set_field("user", user);
+        entry.set_field("tags", vec![FieldValue::from("a"), FieldValue::from(2i64)]);
+
+        let row = flatten_entry(&entry).unwrap();
+        let keys: Vec<&str> = row.iter().map(|(k, _)| k.as_str()).collect();
+        assert_eq!(
+            keys,
+            [
+                "timestamp",
+                "host",
+                "service",
+                "level",
+                "message",
+                "fields.user.id",
+                "fields.tags"
+            ]
+        );
+        assert_eq!(row[5].1, "7");
+        assert_eq!(row[6].1, r#"["a",2]"#);
+    }
+
+    #[test]
+    fn csv_header_covers_columns_of_later_batches() {
+        let path = std::env::temp_dir().join(format!("lognorm-{}.csv", std::process::id()));
+        let path = path.to_str().unwrap();
+        let mut first = LogEntry {
+            message: Some("two\nlines".into()),
+            ..Default::default()
+        };
+        first.set_field("user", "ann");
+        let mut later = LogEntry::default();
+        later.set_field("req_id", "a,b");
+
+        let mut writer = create_writer(path).unwrap();
+        writer.write_batch(&[first]).unwrap();
+        writer.write_batch(&[]).unwrap();
+        writer.write_batch(&[later]).unwrap();
+        writer.finish().unwrap();
+        let csv = std::fs::read_to_string(path).unwrap();
+        std::fs::remove_file(path).unwrap();
+        assert_eq!(
+            csv,
+            "timestamp,host,service,level,message,fields.user,fields.req_id\n\
+             ,,,,\"two\nlines\",ann,\n\
+             ,,,,,,\"a,b\"\n"
+        );
+    }
+
+    #[test]
+    fn tsv_streams_rows_when_columns_are_stable() {
+        let path = std::env::temp_dir().join(format!("lognorm-{}.tsv", std::process::id()));
+        let path = path.to_str().unwrap();
+        let entry = |message: &str| LogEntry {
+            message: Some(message.into()),
+            ..Default::default()
+        };
+
+        let mut writer = create_writer(path).unwrap();
+        writer.write_batch(&[entry("say \"hi")]).unwrap();
+        let Writer::TsvFile(table) = &mut writer else {
+            panic!("not a TSV writer");
+        };
+        table.writer.flush().unwrap();
+        assert_eq!(
+            std::fs::read_to_string(path).unwrap(),
+            "timestamp\thost\tservice\tlevel\tmessage\n\t\t\t\tsay \"hi\n"
+        );
+        writer.write_batch(&[entry("tab\there")]).unwrap();
+        writer.finish().unwrap();
+        let tsv = std::fs::read_to_string(path).unwrap();
+        std::fs::remove_file(path).unwrap();
+        assert!(tsv.ends_with("\n\t\t\t\ttab here\n"));
+    }
+}
@@ -0,0 +1,268 @@
+//! Helpers shared by the access-log scanners (nginx, apache and the Django
+//! and Werkzeug development servers).
+
+use crate::config::HttpRequest;
+use std::borrow::Cow;
+
+/// Build the typed request details from the pieces of an access-log line.
+///
+/// `ident_user` is the `ident authuser` span between the client address and
+/// the `[timestamp]`, `request` the quoted request line and `tail` everything
+/// after the three-digit status code. A trailing response time is read in
+/// `unit`.
+pub(crate) fn http_request(
+    ident_user: &[u8],
+    request: &str,
+    status: u16,
+    tail: &[u8],
+    unit: DurationUnit,
+) -> HttpRequest {
+    let mut parts = request.splitn(3, ' ');
+    let method = parts.next().filter(|m| !m.is_empty());
+    let path = parts.next();
+    let protocol = parts.next();
+
+    let remote_user = ident_user
+        .split(|&b| b == b' ')
+        .filter(|t| !t.is_empty())
+        .nth(1)
+        .and_then(non_dash);
+
+    let mut http = HttpRequest {
+        method: method.map(str::to_string),
+        path: path.map(str::to_string),
+        protocol: protocol.map(str::to_string),
+        status: Some(status),
+        remote_user,
+        ..Default::default()
+    };
+
+    // `bytes ["referrer" "user-agent" ...] [duration]`
+    let mut quoted_seen = 0;
+    for (i, (token, quoted)) in Tokens::new(tail).enumerate() {
+        if i == 0 {
+            http.bytes = match token {
+                b"-" => Some(0),
+                _ => ascii_str(token).and_then(|s| s.parse().ok()),
+            };
+            continue;
+        }
+        if quoted {
+            match quoted_seen {
+                0 => http.referrer = non_dash(token),
+                1 => http.user_agent = non_dash(token),
+                _ => {}
+            }
+            quoted_seen += 1;
+        } else if http.duration_ms.is_none() {
+            http.duration_ms = ascii_str(token).and_then(|t| unit.to_ms(t));
+        }
+    }
+
+    http
+}
+
+/// Whether `line` has a CLF `[dd/Mon/yyyy:hh:mm:ss zone]` timestamp, as
+/// written by nginx and apache (but not the development servers).
+pub(crate) fn has_clf_timestamp(line: &[u8]) -> bool {
+    let Some(open) = memchr::memchr(b'[', line) else {
+        return false;
+    };
+    let ts = &line[open + 1..];
+    ts.len() >= 21
+        && ts[..2].iter().all(u8::is_ascii_digit)
+        && ts[2] == b'/'
+        && ts[6] == b'/'
+        && ts[7..11].iter().all(u8::is_ascii_digit)
+        && ts[11] == b':'
+}
+
+/// `[31/Aug/2025 22:50:01] "GET / HTTP/1.1" 200 1234`, the request line of
+/// the Django and Werkzeug development servers (after any client address).
+/// Returns the timestamp text and the request.
+pub(crate) fn dev_server_request(s: &str) -> Option<(&str, HttpRequest)> {
+    let (timestamp, rest) = s.strip_prefix('[')?.split_once("] ")?;
+    Some((timestamp, quoted_request(rest)?))
+}
+
+/// Whether `s` is a request line [`dev_server_request`] accepts, without
+/// building the request.
+pub(crate) fn is_dev_server_request(s: &str) -> bool {
+    s.strip_prefix('[')
+        .and_then(|s| s.split_once("] "))
+        .is_some_and(|(_, rest)| split_quoted_request(rest).is_some())
+}
+
+/// `"GET / HTTP/1.1" 200 1234`, the message of a development server request.
+pub(crate) fn quoted_request(s: &str) -> Option<HttpRequest> {
+    let (request, status, tail) = split_quoted_request(s)?;
+    Some(http_request(
+        b"",
+        request,
+        status,
+        tail.as_bytes(),
+        DurationUnit::Seconds,
+    ))
+}
+
+/// Whether `s` is a message [`quoted_request`] accepts, without building the
+/// request.
+pub(crate) fn is_quoted_request(s: &str) -> bool {
+    split_quoted_request(s).is_some()
+}
+
+/// The request line, status and tail of a quoted development server request.
+fn split_quoted_request(s: &str) -> Option<(&str, u16, &str)> {
+    let rest = s.strip_prefix('"')?;
+    let close = rest.rfind("\" ")?;
+    let request = &rest[..close];
+    let tail = &rest[close + 2..];
+    let (status, tail) = tail.split_once(' ').unwrap_or((tail, ""));
+    if status.len() != 3 {
+        return None;
+    }
+    Some((request, status.parse().ok()?, tail))
+}
+
+/// Remove ANSI color sequences (`ESC [ ... letter`), which the development
+/// servers add when writing to a terminal.
+pub(crate) fn strip_ansi(s: &str) -> Cow<'_, str> {
+    if !s.contains('\x1b') {
+        return Cow::Borrowed(s);
+    }
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            if chars.clone().next() == Some('[') {
+                for c in chars.by_ref().skip(1) {
+                    if c.is_ascii_alphabetic() {
+                        break;
+                    }
+                }
+            }
+            continue;
+        }
+        out.push(c);
+    }
+    Cow::Owned(out)
+}
+
+/// Unit of the response time logged after the user agent, fixed by the
+/// directive that writes it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum DurationUnit {
+    /// nginx `$request_time` (`0.250`), apache `%T` (`2`).
+    Seconds,
+    /// apache `%D`.
+    Micros,
+}
+
+impl DurationUnit {
+    fn to_ms(self, token: &str) -> Option<f64> {
+        let value = token.parse::<f64>().ok()?;
+        Some(match self {
+            DurationUnit::Seconds => value * 1000.0,
+            DurationUnit::Micros => value / 1000.0,
+        })
+    }
+}
+
+fn non_dash(token: &[u8]) -> Option<String> {
+    match token {
+        b"" | b"-" => None,
+        _ => Some(String::from_utf8_lossy(token).into_owned()),
+    }
+}
+
+fn ascii_str(token: &[u8]) -> Option<&str> {
+    std::str::from_utf8(token).ok()
+}
+
+/// Space separated tokens, where `"..."` (with backslash escapes) is one
+/// token. Yields `(token, was_quoted)`; quotes are stripped.
+struct Tokens<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Tokens<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, pos: 0 }
+    }
+}
+
+impl<'a> Iterator for Tokens<'a> {
+    type Item = (&'a [u8], bool);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let bytes = self.bytes;
+        let len = bytes.len();
+        while self.pos < len && bytes[self.pos] == b' ' {
+            self.pos += 1;
+        }
+        if self.pos >= len {
+            return None;
+        }
+
+        if bytes[self.pos] == b'"' {
+            let start = self.pos + 1;
+            let mut i = start;
+            while i < len && bytes[i] != b'"' {
+                if bytes[i] == b'\\' {
+                    i += 1;
+                }
+                i += 1;
+            }
+            let end = i.min(len);
+            self.pos = (end + 1).min(len);
+            Some((&bytes[start..end], true))
+        } else {
+            let start = self.pos;
+            while self.pos < len && bytes[self.pos] != b' ' {
+                self.pos += 1;
+            }
+            Some((&bytes[start..self.pos], false))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn combined_with_response_time() {
+        let http = http_request(
+            b" - frank ",
+            "GET /a?b=1 HTTP/2.0",
+            404,
+            br#" 5041 "https://ref/" "Mozilla/5.0 \"x\"" 3885"#,
+            DurationUnit::Micros,
+        );
+        assert_eq!(http.method.as_deref(), Some("GET"));
+        assert_eq!(http.path.as_deref(), Some("/a?b=1"));
+        assert_eq!(http.protocol.as_deref(), Some("HTTP/2.0"));
+        assert_eq!(http.remote_user.as_deref(), Some("frank"));
+        assert_eq!(http.bytes, Some(5041));
+        assert_eq!(http.referrer.as_deref(), Some("https://ref/"));
+        assert_eq!(http.user_agent.as_deref(), Some(r#"Mozilla/5.0 \"x\""#));
+        assert_eq!(http.duration_ms, Some(3.885));
+
+        let common = http_request(b" - - ", "GET /", 200, b" -", DurationUnit::Micros);
+        assert_eq!(common.bytes, Some(0));
+        assert_eq!(common.protocol, None);
+        assert_eq!(common.referrer, None);
+        let tail = b" 1 \"-\" \"-\" 0.250";
+        assert_eq!(
+            http_request(b"", "GET /", 200, tail, DurationUnit::Seconds).duration_ms,
+            Some(250.0)
+        );
+        // apache %T: whole seconds
+        let tail = b" 1 \"-\" \"-\" 2";
+        assert_eq!(
+            http_request(b"", "GET /", 200, tail, DurationUnit::Seconds).duration_ms,
+            Some(2000.0)
+        );
+    }
+}
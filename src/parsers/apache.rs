@@ -1,82 +1,67 @@
-use crate::config::LogEntry;
+use crate::config::{HttpRequest, LogEntry};
+use crate::parsers::access::{DurationUnit, has_clf_timestamp, http_request};
+use crate::parsers::{LogParser, parse_chunked};
+use crate::severity::Severity;
 use anyhow::Result;
-use memchr::memchr_iter;
-
-#[cfg(feature = "parallel")]
-use rayon::prelude::*;
+use memchr::{memchr, memchr_iter, memmem};
 
 /// Tunables
-const CHUNK_BYTES: usize = 4 * 1024 * 1024; // 4MB chunks
 const MIN_LINE_LEN: usize = 20; // skip tiny lines
 
-// constant strings for service/levels
+// constant strings for service
 static SERVICE_APACHE: &str = "apache";
-static LEVEL_INFO: &str = "info";
-static LEVEL_WARN: &str = "warn";
-static LEVEL_ERROR: &str = "error";
 
 /// Main entry
 pub fn parse_apache(input: &str) -> Result<Vec<LogEntry>> {
-    #[cfg(feature = "parallel")]
-    {
-        if input.len() > CHUNK_BYTES {
-            return Ok(parse_parallel(input));
-        }
+    Ok(parse_chunked(&ApacheParser, input.as_bytes()))
+}
+
+/// Registry adapter for [`parse_apache`].
+pub struct ApacheParser;
+
+impl LogParser for ApacheParser {
+    fn name(&self) -> &str {
+        "apache"
     }
 
-    // Single-thread fallback
-    Ok(parse_single(input.as_bytes()))
-}
+    fn description(&self) -> &str {
+        "Apache HTTP Server access logs (common/combined) and error logs"
+    }
 
-#[cfg(feature = "parallel")]
-fn parse_parallel(input: &str) -> Vec<LogEntry> {
-    let bytes = input.as_bytes();
-    let len = bytes.len();
+    fn parse_line(&self, line: &[u8]) -> Option<LogEntry> {
+        parse_line(line)
+    }
 
-    // build ranges aligned to newline boundaries
-    let mut ranges = Vec::<std::ops::Range<usize>>::new();
-    let mut start = 0usize;
+    fn parse_chunk(&self, bytes: &[u8], out: &mut Vec<LogEntry>) {
+        parse_chunk_into_vec(bytes, out)
+    }
 
-    while start < len {
-        let mut end = (start + CHUNK_BYTES).min(len);
-        if end < len {
-            while end < len && bytes[end] != b'\n' {
-                end += 1;
-            }
-            if end < len {
-                end += 1; // include newline
-            }
-        } else {
-            end = len;
-        }
-        ranges.push(start..end);
-        start = end;
-    }
-
-    // Parse each range in parallel
-    let parts: Vec<Vec<LogEntry>> = ranges
-        .into_par_iter()
-        .map(|r| {
-            let approx_lines = (r.end - r.start) / 100;
-            let mut out = Vec::with_capacity(approx_lines);
-            parse_chunk_into_vec(&bytes[r], &mut out);
-            out
-        })
-        .collect();
-
-    // Flatten
-    let total: usize = parts.iter().map(Vec::len).sum();
-    let mut result = Vec::with_capacity(total);
-    for mut p in parts {
-        result.append(&mut p);
-    }
-    result
+    fn sniff(&self, line: &[u8]) -> bool {
+        (has_clf_timestamp(line) || is_error_line(line)) && parse_line(line).is_some()
+    }
+
+    fn sniff_specific(&self, line: &[u8]) -> bool {
+        has_clf_timestamp(line) && has_apache_only_tail(line)
+    }
 }
 
-fn parse_single(bytes: &[u8]) -> Vec<LogEntry> {
-    let mut out = Vec::with_capacity(bytes.len() / 100);
-    parse_chunk_into_vec(bytes, &mut out);
-    out
+/// Whether an access line ends in a way nginx never writes it: in the
+/// `common` format (nginx's predefined format is `combined`), or with `%b`'s
+/// `-` for an empty body (nginx's `$body_bytes_sent` logs `0`).
+fn has_apache_only_tail(line: &[u8]) -> bool {
+    let Some(open) = memmem::find(line, b"] \"") else {
+        return false;
+    };
+    let Some(close) = memchr(b'"', &line[open + 3..]) else {
+        return false;
+    };
+    let mut tail = line[open + 3 + close + 1..]
+        .split(|&b| b == b' ')
+        .filter(|token| !token.is_empty());
+    let (Some(_status), Some(bytes)) = (tail.next(), tail.next()) else {
+        return false;
+    };
+    bytes == b"-" || tail.next().is_none()
 }
 
 /// Parse chunk into `out`
@@ -84,26 +69,29 @@ fn parse_chunk_into_vec(bytes: &[u8], out: &mut Vec<LogEntry>) {
     let mut start = 0usize;
     for nl in memchr_iter(b'\n', bytes) {
         let line = &bytes[start..nl];
-        if line.len() >= MIN_LINE_LEN {
-            if let Some(entry) = parse_line(line) {
-                out.push(entry);
-            }
+        if line.len() >= MIN_LINE_LEN
+            && let Some(entry) = parse_line(line)
+        {
+            out.push(entry);
         }
         start = nl + 1;
     }
 
     if start < bytes.len() {
         let line = &bytes[start..];
-        if line.len() >= MIN_LINE_LEN {
-            if let Some(entry) = parse_line(line) {
-                out.push(entry);
-            }
+        if line.len() >= MIN_LINE_LEN
+            && let Some(entry) = parse_line(line)
+        {
+            out.push(entry);
         }
     }
 }
 
 /// Parse a single Apache log line
 fn parse_line(line: &[u8]) -> Option<LogEntry> {
+    if is_error_line(line) {
+        return parse_error_line(line);
+    }
     let bytes = line;
     let len = bytes.len();
 
@@ -130,14 +118,15 @@ fn parse_line(line: &[u8]) -> Option<LogEntry> {
     }
 
     let ip_end = ip_end?;
-    let ts_start = ts_start?;
+    // the timestamp follows the client address
+    let ts_start = ts_start.filter(|&ts| ts > ip_end)?;
     let ts_end = ts_end?;
     let req_start = req_start?;
     let req_end = req_end?;
 
-    let ip = unsafe { std::str::from_utf8_unchecked(&bytes[..ip_end]) };
-    let timestamp = unsafe { std::str::from_utf8_unchecked(&bytes[ts_start..ts_end]) };
-    let request = unsafe { std::str::from_utf8_unchecked(&bytes[req_start..req_end]) };
+    let ip = std::str::from_utf8(&bytes[..ip_end]).ok()?;
+    let timestamp = std::str::from_utf8(&bytes[ts_start..ts_end]).ok()?;
+    let request = std::str::from_utf8(&bytes[req_start..req_end]).ok()?;
 
     // Status code parsing
     let mut status_start = req_end + 1;
@@ -147,16 +136,9 @@ fn parse_line(line: &[u8]) -> Option<LogEntry> {
     if status_start + 3 > len {
         return None;
     }
-    let status_str =
-        unsafe { std::str::from_utf8_unchecked(&bytes[status_start..status_start + 3]) };
+    let status_str = std::str::from_utf8(&bytes[status_start..status_start + 3]).ok()?;
     let status_num = fast_parse_status(status_str)?;
-    let level_static = if (400..500).contains(&status_num) {
-        LEVEL_WARN
-    } else if status_num >= 500 {
-        LEVEL_ERROR
-    } else {
-        LEVEL_INFO
-    };
+    let level = Severity::from_http_status(status_num as u16);
 
     // Method + path
     let (method, path) = if let Some(space_idx) = request.find(' ') {
@@ -179,11 +161,151 @@ fn parse_line(line: &[u8]) -> Option<LogEntry> {
         timestamp: Some(timestamp.to_string()),
         host: Some(ip.to_string()),
         service: Some(SERVICE_APACHE.to_string()),
-        level: Some(level_static.to_string()),
+        level: Some(level),
         message: Some(msg),
+        http: Some(http_request(
+            &bytes[ip_end..ts_start - 1],
+            request,
+            status_num as u16,
+            &bytes[status_start + 3..],
+            DurationUnit::Micros,
+        )),
+        ..Default::default()
     })
 }
 
+/// `[Mon May 12 06:25:24[.123456] 2025] ...`, the error log layout.
+fn is_error_line(line: &[u8]) -> bool {
+    line.len() > 27
+        && line[0] == b'['
+        && line[1..4].iter().all(u8::is_ascii_alphabetic)
+        && line[4] == b' '
+        && line[5..8].iter().all(u8::is_ascii_alphabetic)
+        && line[8] == b' '
+        && line[11] == b' '
+        && line[14] == b':'
+}
+
+/// Parse an error log line, in the 2.2 or 2.4 layout:
+///
+/// ```text
+/// [Mon May 12 06:25:24 2025] [error] [client 1.2.3.4] File does not exist: /var/www/x
+/// [Mon May 12 06:25:24.123456 2025] [core:error] [pid 123:tid 456] [client 1.2.3.4:5678]
+///     AH00124: Request exceeded the limit of 10 internal redirects, referer: http://x/
+/// ```
+///
+/// The module, `pid`/`tid`, client port and `AHnnnnn` code become fields;
+/// the client address becomes the host and a trailing `referer` the
+/// `http` referrer.
+fn parse_error_line(line: &[u8]) -> Option<LogEntry> {
+    let s = std::str::from_utf8(line).ok()?.trim_end();
+    let (timestamp, mut rest) = s[1..].split_once("] ")?;
+
+    let mut entry = LogEntry {
+        timestamp: Some(timestamp.to_string()),
+        service: Some(SERVICE_APACHE.to_string()),
+        ..Default::default()
+    };
+
+    // [module:level] or [level]
+    let (tag, after) = rest.strip_prefix('[')?.split_once(']')?;
+    let (module, level) = tag.split_once(':').unwrap_or(("", tag));
+    // 2.4 has trace1 .. trace8
+    let level = match level.strip_prefix("trace") {
+        Some(n) if n.bytes().all(|c| c.is_ascii_digit()) => Severity::Trace,
+        _ => level.parse::<Severity>().ok()?,
+    };
+    entry.level = Some(level);
+    if !module.is_empty() {
+        entry.set_field("module", module);
+    }
+    rest = after.trim_start();
+
+    // [pid 123:tid 456] [client 1.2.3.4:5678] [remote 10.0.0.1:80]
+    while let Some(tag) = rest.strip_prefix('[') {
+        let Some(end) = tag_end(tag) else {
+            break;
+        };
+        let (tag, after) = (&tag[..end], &tag[end + 1..]);
+        let Some((key, value)) = tag.split_once(' ') else {
+            break;
+        };
+        match key {
+            "pid" => {
+                let (pid, tid) = match value.split_once(":tid ") {
+                    Some((pid, tid)) => (pid, Some(tid)),
+                    None => (value, None),
+                };
+                entry.set_field("pid", pid.parse::<i64>().ok()?);
+                if let Some(tid) = tid.and_then(|t| t.parse::<i64>().ok()) {
+                    entry.set_field("tid", tid);
+                }
+            }
+            "client" => {
+                let (addr, port) = split_port(value);
+                entry.host = Some(addr.to_string());
+                if let Some(port) = port {
+                    entry.set_field("client_port", port);
+                }
+            }
+            "remote" => entry.set_field("remote", value),
+            _ => break,
+        }
+        rest = after.trim_start();
+    }
+
+    // AH00124: message
+    if let Some((code, message)) = rest.split_once(": ")
+        && code.len() == 7
+        && code.starts_with("AH")
+        && code[2..].bytes().all(|c| c.is_ascii_digit())
+    {
+        entry.set_field("error_code", code);
+        rest = message;
+    }
+    if let Some(at) = rest.rfind(", referer: ") {
+        entry.http = Some(HttpRequest {
+            referrer: Some(rest[at + 11..].to_string()),
+            ..Default::default()
+        });
+        rest = &rest[..at];
+    }
+    entry.message = Some(rest.to_string());
+    Some(entry)
+}
+
+/// Index of the `]` closing a tag, past any bracketed IPv6 address inside
+/// it (`client [::1]:52022]`).
+fn tag_end(tag: &str) -> Option<usize> {
+    let mut depth = 0;
+    for (i, b) in tag.bytes().enumerate() {
+        match b {
+            b'[' => depth += 1,
+            b']' if depth == 0 => return Some(i),
+            b']' => depth -= 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+/// `1.2.3.4:5678` into the address and port; IPv6 addresses without a
+/// port are left whole.
+fn split_port(client: &str) -> (&str, Option<i64>) {
+    match client.rsplit_once(':') {
+        Some((addr, port))
+            if (addr.contains('.') || addr.ends_with(']'))
+                && let Ok(port) = port.parse() =>
+        {
+            (
+                addr.trim_start_matches('[').trim_end_matches(']'),
+                Some(port),
+            )
+        }
+        _ => (client, None),
+    }
+}
+
 #[inline(always)]
 fn fast_parse_status(status_str: &str) -> Option<usize> {
     let b = status_str.as_bytes();
@@ -202,6 +324,7 @@ fn fast_parse_status(status_str: &str) -> Option<usize> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::FieldValue;
 
     const SAMPLE: &str = r#"233.223.117.90 - - [27/Dec/2037:12:00:00 +0530] "DELETE /usr/admin HTTP/1.0" 502 4963 "-" "Mozilla/5.0" 45
 162.253.4.179 - - [27/Dec/2037:12:00:00 +0530] "GET /usr/admin/developer HTTP/1.0" 200 5041 "-" "Mozilla/5.0" 3885
@@ -212,7 +335,92 @@ mod tests {
         let v = parse_apache(SAMPLE).unwrap();
         assert_eq!(v.len(), 2);
         assert_eq!(v[0].host.as_deref(), Some("233.223.117.90"));
-        assert_eq!(v[0].level.as_deref(), Some("error")); // 502
-        assert_eq!(v[1].level.as_deref(), Some("info")); // 200
+        assert_eq!(v[0].level, Some(Severity::Error)); // 502
+        assert_eq!(v[1].level, Some(Severity::Info)); // 200
+
+        let http = v[1].http.as_ref().unwrap();
+        assert_eq!(http.method.as_deref(), Some("GET"));
+        assert_eq!(http.path.as_deref(), Some("/usr/admin/developer"));
+        assert_eq!(http.protocol.as_deref(), Some("HTTP/1.0"));
+        assert_eq!(http.status, Some(200));
+        assert_eq!(http.bytes, Some(5041));
+        assert_eq!(http.referrer, None);
+        assert_eq!(http.user_agent.as_deref(), Some("Mozilla/5.0"));
+        assert_eq!(http.duration_ms, Some(3.885)); // %D microseconds
+    }
+
+    #[test]
+    fn parses_error_lines() {
+        let error = concat!(
+            "[Mon May 12 06:25:24 2025] [error] [client 1.2.3.4] File does not exist: /var/www/favicon.ico\n",
+            "[Mon May 12 06:25:24.123456 2025] [core:error] [pid 123:tid 456] [client 1.2.3.4:5678] ",
+            "AH00124: Request exceeded the limit of 10 internal redirects, referer: http://example.com/\n",
+            "[Mon May 12 06:25:25.000001 2025] [proxy:trace2] [pid 124] proxy_util.c(2153): connecting\n",
+        );
+        let v = parse_apache(error).unwrap();
+        assert_eq!(v.len(), 3);
+        assert_eq!(v[0].timestamp.as_deref(), Some("Mon May 12 06:25:24 2025"));
+        assert_eq!(v[0].level, Some(Severity::Error));
+        assert_eq!(v[0].host.as_deref(), Some("1.2.3.4"));
+        assert_eq!(
+            v[0].message.as_deref(),
+            Some("File does not exist: /var/www/favicon.ico")
+        );
+        assert_eq!(v[1].fields.get("module"), Some(&FieldValue::from("core")));
+        assert_eq!(v[1].fields.get("pid"), Some(&FieldValue::Int(123)));
+        assert_eq!(v[1].fields.get("tid"), Some(&FieldValue::Int(456)));
+        assert_eq!(v[1].fields.get("client_port"), Some(&FieldValue::Int(5678)));
+        assert_eq!(
+            v[1].fields.get("error_code"),
+            Some(&FieldValue::from("AH00124"))
+        );
+        assert_eq!(
+            v[1].message.as_deref(),
+            Some("Request exceeded the limit of 10 internal redirects")
+        );
+        assert_eq!(
+            v[1].http.as_ref().unwrap().referrer.as_deref(),
+            Some("http://example.com/")
+        );
+        assert_eq!(v[2].level, Some(Severity::Trace));
+        assert_eq!(v[2].host, None);
+        assert_eq!(
+            v[2].message.as_deref(),
+            Some("proxy_util.c(2153): connecting")
+        );
+        assert!(ApacheParser.sniff(error.as_bytes()));
+    }
+
+    #[test]
+    fn parses_ipv6_clients() {
+        let line = concat!(
+            "[Mon May 12 06:25:24.123456 2025] [core:error] [pid 123] ",
+            "[client [::1]:52022] AH00126: Invalid URI",
+        );
+        let entry = ApacheParser.parse_line(line.as_bytes()).unwrap();
+        assert_eq!(entry.host.as_deref(), Some("::1"));
+        assert_eq!(
+            entry.fields.get("client_port"),
+            Some(&FieldValue::Int(52022))
+        );
+        assert_eq!(
+            entry.fields.get("error_code"),
+            Some(&FieldValue::from("AH00126"))
+        );
+        assert_eq!(entry.message.as_deref(), Some("Invalid URI"));
+    }
+
+    #[test]
+    fn rejects_error_lines_with_unknown_tags() {
+        assert!(
+            ApacheParser
+                .parse_line(b"[Mon May 12 06:25:24 2025] [bogus] message")
+                .is_none()
+        );
+        assert!(
+            ApacheParser
+                .parse_line(b"[Mon May 12 06:25:24 2025] [error] [pid x] message")
+                .is_none()
+        );
     }
 }
@@ -0,0 +1,403 @@
+//! Container runtime log files: Docker's `json-file` driver
+//!
+//! ```text
+//! {"log":"GET / 200\n","stream":"stdout","time":"2025-05-12T06:25:24.123456789Z"}
+//! ```
+//!
+//! and the CRI format written by containerd and CRI-O under
+//! `/var/log/pods` (`2025-05-12T06:25:24.123456789Z stdout F GET / 200`).
+//!
+//! Long lines are split by the runtime: Docker leaves the trailing `\n` off
+//! every piece but the last, CRI tags pieces `P` (partial) up to the final
+//! `F`. Pieces are joined back into one record per stream, and chunks are
+//! only cut after a final piece. The stream is kept as the `stream` field.
+//! With [`with_payload`](ContainerParser::with_payload) the joined message
+//! is parsed by another preset, falling back to the raw text.
+
+use crate::config::LogEntry;
+use crate::parsers::LogParser;
+use memchr::{memchr, memchr_iter, memmem, memrchr};
+use serde::Deserialize;
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// Streams whose pieces are joined separately.
+const STREAMS: [&str; 2] = ["stdout", "stderr"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Runtime {
+    Docker,
+    Cri,
+}
+
+pub struct ContainerParser {
+    runtime: Runtime,
+    payload: Option<Arc<dyn LogParser>>,
+}
+
+impl ContainerParser {
+    /// Docker `json-file` logs, as the `docker` preset.
+    pub fn docker() -> Self {
+        Self {
+            runtime: Runtime::Docker,
+            payload: None,
+        }
+    }
+
+    /// CRI (containerd, CRI-O) logs, as the `cri` preset.
+    pub fn cri() -> Self {
+        Self {
+            runtime: Runtime::Cri,
+            payload: None,
+        }
+    }
+
+    /// Parse each message with `parser`, e.g. the `json` preset for
+    /// containers that log JSON lines.
+    pub fn with_payload(mut self, parser: Arc<dyn LogParser>) -> Self {
+        self.payload = Some(parser);
+        self
+    }
+
+    fn piece(&self, line: &[u8]) -> Option<Piece> {
+        match self.runtime {
+            Runtime::Docker => docker_piece(line),
+            Runtime::Cri => cri_piece(line),
+        }
+    }
+
+    fn build_entry(&self, piece: Piece) -> LogEntry {
+        let mut entry = self
+            .payload
+            .as_ref()
+            .and_then(|p| p.parse_line(piece.message.as_bytes()))
+            .unwrap_or_else(|| LogEntry {
+                message: Some(piece.message),
+                ..Default::default()
+            });
+        if entry.timestamp.is_none() {
+            entry.timestamp = Some(piece.time);
+        }
+        entry.set_field("stream", piece.stream);
+        for (key, value) in piece.attrs {
+            entry.set_field(key.as_str(), value);
+        }
+        entry
+    }
+
+    /// Whether the last piece of `stream` before the line starting at `end`
+    /// is partial. Scans back no further than the start of `bytes`, which
+    /// is a record boundary where no stream is partial, and only parses the
+    /// lines that mention the stream name.
+    fn open_before(&self, bytes: &[u8], end: usize, stream: &str) -> bool {
+        let mut end = end;
+        while let Some(i) = memmem::rfind(&bytes[..end], stream.as_bytes()) {
+            let line_start = memrchr(b'\n', &bytes[..i]).map_or(0, |j| j + 1);
+            let line_end = memchr(b'\n', &bytes[i..]).map_or(bytes.len(), |j| i + j);
+            if let Some(piece) = self.piece(&bytes[line_start..line_end])
+                && piece.stream == stream
+            {
+                return piece.partial;
+            }
+            end = line_start;
+        }
+        false
+    }
+}
+
+impl LogParser for ContainerParser {
+    fn name(&self) -> &str {
+        match self.runtime {
+            Runtime::Docker => "docker",
+            Runtime::Cri => "cri",
+        }
+    }
+
+    fn aliases(&self) -> &[&str] {
+        match self.runtime {
+            Runtime::Docker => &["docker_json", "json-file"],
+            Runtime::Cri => &["containerd", "crio", "kubernetes", "k8s"],
+        }
+    }
+
+    fn description(&self) -> &str {
+        match self.runtime {
+            Runtime::Docker => "Docker json-file container logs",
+            Runtime::Cri => "CRI container logs (containerd, CRI-O, Kubernetes)",
+        }
+    }
+
+    /// A single piece, partial or not, as a record.
+    fn parse_line(&self, line: &[u8]) -> Option<LogEntry> {
+        Some(self.build_entry(self.piece(line)?))
+    }
+
+    fn parse_chunk(&self, bytes: &[u8], out: &mut Vec<LogEntry>) {
+        // partial pieces per stream, until their final piece
+        let mut pending: Vec<Piece> = Vec::new();
+        let mut start = 0;
+        let ends =
+            memchr_iter(b'\n', bytes).chain((!bytes.ends_with(b"\n")).then_some(bytes.len()));
+        for end in ends {
+            let line = &bytes[start..end];
+            start = end + 1;
+            let Some(piece) = self.piece(line) else {
+                continue;
+            };
+
+            let joined = match pending.iter().position(|p| p.stream == piece.stream) {
+                Some(i) => {
+                    let mut head = pending.remove(i);
+                    head.message.push_str(&piece.message);
+                    head.partial = piece.partial;
+                    head
+                }
+                None => piece,
+            };
+            if joined.partial {
+                pending.push(joined);
+            } else {
+                out.push(self.build_entry(joined));
+            }
+        }
+        // input ended mid-record
+        for piece in pending {
+            out.push(self.build_entry(piece));
+        }
+    }
+
+    /// The first line at or after `pos` where neither stream is inside a
+    /// partial record.
+    fn record_start(&self, bytes: &[u8], pos: usize) -> usize {
+        if pos == 0 {
+            return 0;
+        }
+        let Some(nl) = bytes.get(pos - 1..).and_then(|rest| memchr(b'\n', rest)) else {
+            return bytes.len();
+        };
+        let mut start = pos + nl;
+        let mut open = STREAMS.map(|stream| self.open_before(bytes, start, stream));
+        while start < bytes.len() {
+            if !open.contains(&true) {
+                return start;
+            }
+            let end = memchr(b'\n', &bytes[start..]).map_or(bytes.len(), |i| start + i);
+            if let Some(piece) = self.piece(&bytes[start..end])
+                && let Some(i) = STREAMS.iter().position(|s| *s == piece.stream)
+            {
+                open[i] = piece.partial;
+            }
+            start = end + 1;
+        }
+        bytes.len()
+    }
+
+    fn groups_lines(&self) -> bool {
+        true
+    }
+
+    fn sniff(&self, line: &[u8]) -> bool {
+        self.piece(line).is_some()
+    }
+}
+
+/// One line of a container log file.
+struct Piece {
+    time: String,
+    stream: String,
+    partial: bool,
+    message: String,
+    /// Docker `--log-opt labels/env` attributes.
+    attrs: BTreeMap<String, String>,
+}
+
+#[derive(Deserialize)]
+struct DockerLine<'a> {
+    #[serde(borrow)]
+    log: Cow<'a, str>,
+    #[serde(borrow)]
+    stream: Cow<'a, str>,
+    #[serde(borrow)]
+    time: Cow<'a, str>,
+    #[serde(default)]
+    attrs: BTreeMap<String, String>,
+}
+
+fn docker_piece(line: &[u8]) -> Option<Piece> {
+    let line = line.trim_ascii();
+    if !line.starts_with(b"{") {
+        return None;
+    }
+    let DockerLine {
+        log,
+        stream,
+        time,
+        attrs,
+    } = serde_json::from_slice(line).ok()?;
+    let message = log.strip_suffix('\n');
+    Some(Piece {
+        time: time.into_owned(),
+        stream: stream.into_owned(),
+        partial: message.is_none(),
+        message: message
+            .map_or(log.as_ref(), |m| m.strip_suffix('\r').unwrap_or(m))
+            .to_string(),
+        attrs,
+    })
+}
+
+/// `<RFC 3339 time> <stdout|stderr> <P|F>[:tags] <message>`
+fn cri_piece(line: &[u8]) -> Option<Piece> {
+    let line = std::str::from_utf8(line).ok()?;
+    let line = line.strip_suffix('\r').unwrap_or(line);
+    let (time, rest) = line.split_once(' ')?;
+    if !(time.len() >= 20 && time.as_bytes()[0].is_ascii_digit() && time.as_bytes()[10] == b'T') {
+        return None;
+    }
+    let (stream, rest) = rest.split_once(' ')?;
+    if !matches!(stream, "stdout" | "stderr") {
+        return None;
+    }
+    let (tag, message) = rest.split_once(' ').unwrap_or((rest, ""));
+    let partial = match tag.split(':').next() {
+        Some("P") => true,
+        Some("F") => false,
+        _ => return None,
+    };
+    Some(Piece {
+        time: time.to_string(),
+        stream: stream.to_string(),
+        partial,
+        message: message.to_string(),
+        attrs: BTreeMap::new(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::FieldValue;
+    use crate::parsers::json::JsonParser;
+    use crate::parsers::python_web::PythonWebParser;
+    use crate::severity::Severity;
+
+    const DOCKER: &str = concat!(
+        r#"{"log":"first half, ","stream":"stdout","time":"2025-05-12T06:25:24.1Z"}"#,
+        "\n",
+        r#"{"log":"oops\n","stream":"stderr","time":"2025-05-12T06:25:24.2Z","attrs":{"tag":"api"}}"#,
+        "\n",
+        r#"{"log":"second half\n","stream":"stdout","time":"2025-05-12T06:25:24.3Z"}"#,
+        "\n",
+        r#"{"log":"done\n","stream":"stderr","time":"2025-05-12T06:25:24.4Z"}"#,
+        "\n",
+    );
+
+    const CRI: &str = "2025-05-12T06:25:24.123456789Z stdout P {\"level\":\"warn\",\n\
+                       2025-05-12T06:25:24.123456789Z stdout F \"msg\":\"slow\"}\n\
+                       2025-05-12T06:25:25.000000000Z stderr F plain text\n\
+                       not a cri line\n";
+
+    #[test]
+    fn docker_joins_partial_pieces_per_stream() {
+        let v = ContainerParser::docker().parse(DOCKER).unwrap();
+        assert_eq!(v.len(), 3);
+        assert_eq!(v[0].message.as_deref(), Some("oops"));
+        assert_eq!(v[0].fields.get("stream"), Some(&FieldValue::from("stderr")));
+        assert_eq!(v[0].fields.get("tag"), Some(&FieldValue::from("api")));
+        assert_eq!(v[1].timestamp.as_deref(), Some("2025-05-12T06:25:24.1Z"));
+        assert_eq!(v[1].message.as_deref(), Some("first half, second half"));
+        assert_eq!(v[2].message.as_deref(), Some("done"));
+    }
+
+    #[test]
+    fn docker_record_start_skips_open_records() {
+        let parser = ContainerParser::docker();
+        // the stderr line sits inside the open stdout record: no cut after it
+        let done = DOCKER.find(r#"{"log":"done"#).unwrap();
+        assert_eq!(parser.record_start(DOCKER.as_bytes(), 1), done);
+        assert_eq!(parser.record_start(DOCKER.as_bytes(), done), done);
+    }
+
+    #[test]
+    fn cri_joins_partial_pieces_and_parses_the_payload() {
+        let parser = ContainerParser::cri().with_payload(Arc::new(JsonParser::default()));
+        let v = parser.parse(CRI).unwrap();
+        assert_eq!(v.len(), 2);
+        assert_eq!(v[0].level, Some(Severity::Warn));
+        assert_eq!(v[0].message.as_deref(), Some("slow"));
+        assert_eq!(
+            v[0].timestamp.as_deref(),
+            Some("2025-05-12T06:25:24.123456789Z")
+        );
+        assert_eq!(v[1].message.as_deref(), Some("plain text"));
+        assert_eq!(v[1].fields.get("stream"), Some(&FieldValue::from("stderr")));
+    }
+
+    #[test]
+    fn cri_record_start_never_cuts_a_partial_record() {
+        assert_eq!(
+            ContainerParser::cri().record_start(CRI.as_bytes(), 1),
+            CRI.find("2025-05-12T06:25:25").unwrap()
+        );
+    }
+
+    #[test]
+    fn docker_rejects_pieces_without_a_stream() {
+        assert!(
+            ContainerParser::docker()
+                .parse_line(br#"{"log":"no stream\n"}"#)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn cri_rejects_unknown_streams_and_tags() {
+        let cri = ContainerParser::cri();
+        assert!(
+            cri.parse_line(b"2025-05-12T06:25:24Z stdin F message")
+                .is_none()
+        );
+        assert!(
+            cri.parse_line(b"2025-05-12T06:25:24Z stdout X message")
+                .is_none()
+        );
+        assert!(!cri.sniff(b"not a cri line"));
+    }
+
+    #[test]
+    fn payload_that_does_not_match_keeps_the_raw_message() {
+        let line = r#"{"log":"INFO\n","stream":"stdout","time":"2025-05-12T06:25:24.1Z"}"#;
+        let parser = ContainerParser::docker().with_payload(Arc::new(PythonWebParser));
+        let v = parser.parse(line).unwrap();
+        assert_eq!(v.len(), 1);
+        assert_eq!(v[0].message.as_deref(), Some("INFO"));
+        assert_eq!(v[0].level, None);
+        assert_eq!(v[0].timestamp.as_deref(), Some("2025-05-12T06:25:24.1Z"));
+    }
+
+    #[test]
+    fn record_start_only_looks_back_to_the_start_of_bytes() {
+        let cri = "2025-05-12T06:25:24Z stdout P a\n\
+                   2025-05-12T06:25:24Z stdout F b\n\
+                   2025-05-12T06:25:25Z stderr P c\n\
+                   2025-05-12T06:25:25Z stdout F d\n\
+                   2025-05-12T06:25:26Z stderr F e\n\
+                   2025-05-12T06:25:27Z stdout F f\n";
+        let bytes = cri.as_bytes();
+        let parser = ContainerParser::cri();
+        let cut = parser.record_start(bytes, 1);
+        assert_eq!(cut, cri.find("2025-05-12T06:25:25Z stderr").unwrap());
+        // a batch that starts at the cut sees the same boundaries
+        for pos in cut + 1..=bytes.len() {
+            assert_eq!(
+                cut + parser.record_start(&bytes[cut..], pos - cut),
+                parser.record_start(bytes, pos)
+            );
+        }
+        assert_eq!(
+            parser.record_start(bytes, cut + 1),
+            cri.find("2025-05-12T06:25:27Z").unwrap()
+        );
+    }
+}
@@ -0,0 +1,185 @@
+use std::sync::Arc;
+
+use memchr::memchr_iter;
+
+use super::{LogParser, ParserRegistry, registered};
+
+/// Preset name that asks for format detection instead of a fixed parser.
+pub const AUTO_PRESET: &str = "auto";
+
+/// Default number of non-empty lines sampled by [`detect`].
+pub const DEFAULT_SAMPLE_LINES: usize = 100;
+
+/// Outcome of format detection.
+pub struct Detection {
+    pub parser: Arc<dyn LogParser>,
+    /// Fraction of sampled lines the parser accepted, `0.0..=1.0`.
+    pub confidence: f64,
+    /// Number of non-empty lines that were sampled.
+    pub sampled: usize,
+}
+
+/// Score every parser in the process-wide registry against the first
+/// `max_lines` non-empty lines of `input` and return the best match.
+pub fn detect(input: &[u8], max_lines: usize) -> Option<Detection> {
+    detect_with(registered().iter(), input, max_lines)
+}
+
+impl ParserRegistry {
+    /// Like [`detect`], but scoring the parsers in this registry.
+    pub fn detect(&self, input: &[u8], max_lines: usize) -> Option<Detection> {
+        detect_with(self.iter(), input, max_lines)
+    }
+}
+
+/// Ties go to the parser with more lines it claims as its own (see
+/// [`LogParser::sniff_specific`]), then to the parser registered first; a
+/// parser that accepted no lines never wins.
+fn detect_with<'a>(
+    parsers: impl Iterator<Item = &'a Arc<dyn LogParser>>,
+    input: &[u8],
+    max_lines: usize,
+) -> Option<Detection> {
+    let sample = sample_lines(input, max_lines);
+    if sample.is_empty() {
+        return None;
+    }
+
+    let mut best: Option<(usize, usize, &Arc<dyn LogParser>)> = None;
+    for parser in parsers {
+        let (mut hits, mut specific) = (0, 0);
+        for line in &sample {
+            if parser.sniff(line) {
+                hits += 1;
+                specific += usize::from(parser.sniff_specific(line));
+            }
+        }
+        if hits > 0
+            && best.is_none_or(|(best_hits, best_specific, _)| {
+                (hits, specific) > (best_hits, best_specific)
+            })
+        {
+            best = Some((hits, specific, parser));
+        }
+    }
+
+    best.map(|(hits, _, parser)| Detection {
+        parser: Arc::clone(parser),
+        confidence: hits as f64 / sample.len() as f64,
+        sampled: sample.len(),
+    })
+}
+
+/// First `max_lines` non-empty lines, with any trailing `\r` stripped.
+fn sample_lines(input: &[u8], max_lines: usize) -> Vec<&[u8]> {
+    let mut lines = Vec::with_capacity(max_lines);
+    let mut start = 0;
+    let ends = memchr_iter(b'\n', input).chain(std::iter::once(input.len()));
+    for end in ends {
+        if lines.len() == max_lines {
+            break;
+        }
+        let line = input[start..end]
+            .strip_suffix(b"\r")
+            .unwrap_or(&input[start..end]);
+        if !line.iter().all(u8::is_ascii_whitespace) {
+            lines.push(line);
+        }
+        start = end + 1;
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn picks_best_builtin() {
+        let registry = ParserRegistry::with_builtins();
+        let cases = [
+            (
+                "127.0.0.1 - - [12/May/2025:06:25:24 +0000] \"GET / HTTP/1.1\" 200 612 \"-\" \"curl\"\n",
+                "nginx",
+            ),
+            (
+                "2025/05/12 06:25:24 [warn] 812#812: *1 a client request body is buffered\n",
+                "nginx",
+            ),
+            (
+                "<34>Oct 11 22:14:15 mymachine su: 'su root' failed for lonvick\n",
+                "syslog",
+            ),
+            (
+                "<165>1 2025-10-11T22:14:15.003Z host app 1234 ID47 - An application event\n",
+                "rfc5424",
+            ),
+            (
+                "INFO 2025-08-31 22:50:01,234 views.index Some log message\nnot a log line\n",
+                "python_web",
+            ),
+            (
+                "{\"__REALTIME_TIMESTAMP\":\"1760220855003000\",\"MESSAGE\":\"hi\"}\n",
+                "journal_json",
+            ),
+            (
+                "__CURSOR=s=1\n__REALTIME_TIMESTAMP=1760220855003000\nMESSAGE=hi\n\n",
+                "journal_export",
+            ),
+            (
+                "[31/Aug/2025 22:50:01] \"GET / HTTP/1.1\" 200 1234\n",
+                "django",
+            ),
+            (
+                "127.0.0.1 - - [31/Aug/2025 22:50:01] \"GET / HTTP/1.1\" 200 -\n",
+                "flask",
+            ),
+            (
+                "{\"level\":\"info\",\"ts\":1756680601.5,\"msg\":\"started\"}\n",
+                "json",
+            ),
+            (
+                "time=2025-08-31T22:50:01Z level=info msg=\"started\" port=8080\n",
+                "logfmt",
+            ),
+            (
+                "2025-08-31T22:50:01.234Z [worker] WARN queue is backing up\n",
+                "generic",
+            ),
+            (
+                "[Mon May 12 06:25:24.123456 2025] [core:error] [pid 123:tid 456] AH00124: loop\n",
+                "apache",
+            ),
+            (
+                "10.0.0.1 - - [12/May/2025:06:25:24 +0000] \"GET / HTTP/1.1\" 200 612 \"-\" \"curl\"\n\
+                 10.0.0.1 - - [12/May/2025:06:25:25 +0000] \"HEAD / HTTP/1.1\" 200 - \"-\" \"curl\"\n",
+                "apache",
+            ),
+            (
+                "10.0.0.1 - frank [12/May/2025:06:25:24 +0000] \"GET /a HTTP/1.0\" 200 2326\n",
+                "apache",
+            ),
+            (
+                "{\"log\":\"GET / 200\\n\",\"stream\":\"stdout\",\"time\":\"2025-05-12T06:25:24.1Z\"}\n",
+                "docker",
+            ),
+            (
+                "2025-05-12T06:25:24.123456789Z stderr F level=error msg=boom\n",
+                "cri",
+            ),
+        ];
+        for (input, expected) in cases {
+            let d = registry
+                .detect(input.as_bytes(), DEFAULT_SAMPLE_LINES)
+                .unwrap();
+            assert_eq!(d.parser.name(), expected);
+        }
+
+        let d = registry
+            .detect(cases[4].0.as_bytes(), DEFAULT_SAMPLE_LINES)
+            .unwrap();
+        assert_eq!(d.sampled, 2);
+        assert_eq!(d.confidence, 0.5);
+        assert!(registry.detect(b"\n\n", DEFAULT_SAMPLE_LINES).is_none());
+    }
+}
@@ -0,0 +1,302 @@
+//! Django logs: runserver request lines (`django.server`), `django.request`
+//! errors and `django.db.backends` SQL timing. Lines may be bare (Django's
+//! default console format is just the message) or carry a python_web style
+//! `LEVEL date time logger` header; tracebacks are folded into the entry
+//! above them.
+
+use crate::config::{HttpRequest, LogEntry};
+use crate::parsers::LogParser;
+use crate::parsers::access::{
+    dev_server_request, is_dev_server_request, is_quoted_request, quoted_request, strip_ansi,
+};
+use crate::parsers::multiline::Multiline;
+use crate::parsers::python_web;
+use crate::severity::Severity;
+use std::sync::LazyLock;
+
+const LOGGER_SERVER: &str = "django.server";
+const LOGGER_REQUEST: &str = "django.request";
+const LOGGER_DB: &str = "django.db.backends";
+
+static MULTILINE: LazyLock<Multiline> = LazyLock::new(|| Multiline::new().start_with(is_start));
+
+/// Reason phrases Django logs `django.request` responses with.
+const REASONS: [(&str, u16); 14] = [
+    ("Bad Request", 400),
+    ("Unauthorized", 401),
+    ("Forbidden", 403),
+    ("Not Found", 404),
+    ("Method Not Allowed", 405),
+    ("Gone", 410),
+    ("Request Entity Too Large", 413),
+    ("Content Too Large", 413),
+    ("Too Many Requests", 429),
+    ("Internal Server Error", 500),
+    ("Not Implemented", 501),
+    ("Bad Gateway", 502),
+    ("Service Unavailable", 503),
+    ("Gateway Timeout", 504),
+];
+
+pub struct DjangoParser;
+
+impl LogParser for DjangoParser {
+    fn name(&self) -> &str {
+        "django"
+    }
+
+    fn description(&self) -> &str {
+        "Django runserver, django.request and django.db.backends SQL logs"
+    }
+
+    fn parse_line(&self, line: &[u8]) -> Option<LogEntry> {
+        parse_line(line)
+    }
+
+    fn multiline(&self) -> Option<&Multiline> {
+        Some(&MULTILINE)
+    }
+
+    fn parse_record(&self, first: &[u8], continuation: &[&[u8]]) -> Option<LogEntry> {
+        let entry = parse_line(first)?;
+        Some(python_web::attach_continuation(entry, continuation))
+    }
+}
+
+/// Whether `line` is a record `parse_line` accepts, matched without building
+/// the entry so grouping a record doesn't parse its first line twice.
+fn is_start(line: &[u8]) -> bool {
+    let Ok(line) = std::str::from_utf8(line) else {
+        return false;
+    };
+    let line = strip_ansi(line);
+    let line = line.trim_end();
+    if line.starts_with('[') {
+        return is_dev_server_request(line);
+    }
+    python_web::is_header(line.as_bytes()) || is_known_message(line)
+}
+
+fn parse_line(line: &[u8]) -> Option<LogEntry> {
+    let line = std::str::from_utf8(line).ok()?;
+    let line = strip_ansi(line);
+    let line = line.trim_end();
+
+    // [31/Aug/2025 22:50:01] "GET / HTTP/1.1" 200 1234
+    if line.starts_with('[') {
+        let (timestamp, http) = dev_server_request(line)?;
+        let status = http.status.unwrap_or_default();
+        return Some(LogEntry {
+            timestamp: Some(timestamp.to_string()),
+            service: Some(LOGGER_SERVER.to_string()),
+            level: Some(Severity::from_http_status(status)),
+            message: Some(line[timestamp.len() + 3..].to_string()),
+            http: Some(http),
+            ..Default::default()
+        });
+    }
+
+    if python_web::is_header(line.as_bytes()) {
+        let mut entry = python_web::parse_line(line.as_bytes())?;
+        entry.service = entry.host.take();
+        enrich(&mut entry);
+        return Some(entry);
+    }
+
+    let mut entry = LogEntry {
+        message: Some(line.to_string()),
+        ..Default::default()
+    };
+    enrich(&mut entry).then_some(entry)
+}
+
+/// Whether [`enrich`] recognizes `message`, without changing an entry.
+fn is_known_message(message: &str) -> bool {
+    is_quoted_request(message)
+        || sql_duration(message).is_some()
+        || request_error(message).is_some()
+}
+
+/// Recognize the message of a `django.server`, `django.request` or
+/// `django.db.backends` record. Fills in the logger and level when the line
+/// had no header. Returns whether the message was recognized.
+fn enrich(entry: &mut LogEntry) -> bool {
+    let Some(message) = entry.message.as_deref() else {
+        return false;
+    };
+
+    // "GET / HTTP/1.1" 200 1234
+    if let Some(http) = quoted_request(message) {
+        let status = http.status.unwrap_or_default();
+        entry
+            .service
+            .get_or_insert_with(|| LOGGER_SERVER.to_string());
+        entry
+            .level
+            .get_or_insert(Severity::from_http_status(status));
+        entry.http = Some(http);
+        return true;
+    }
+
+    // (0.002) SELECT ...; args=(1,); alias=default
+    if let Some(query) = parse_sql(message) {
+        entry.service.get_or_insert_with(|| LOGGER_DB.to_string());
+        entry.level.get_or_insert(Severity::Debug);
+        entry.set_field("sql", query.sql);
+        entry.set_field("sql_duration_ms", query.duration_ms);
+        if let Some(args) = query.args {
+            entry.set_field("sql_args", args);
+        }
+        if let Some(alias) = query.alias {
+            entry.set_field("db_alias", alias);
+        }
+        return true;
+    }
+
+    // Not Found: /favicon.ico
+    if let Some((path, status)) = request_error(message) {
+        let http = HttpRequest {
+            path: Some(path.to_string()),
+            status: Some(status),
+            ..Default::default()
+        };
+        entry
+            .service
+            .get_or_insert_with(|| LOGGER_REQUEST.to_string());
+        entry
+            .level
+            .get_or_insert(Severity::from_http_status(status));
+        entry.http = Some(http);
+        return true;
+    }
+
+    false
+}
+
+/// `Not Found: /favicon.ico`: the path and the status of the reason phrase.
+fn request_error(message: &str) -> Option<(&str, u16)> {
+    let (reason, path) = message.split_once(": ")?;
+    let &(_, status) = REASONS.iter().find(|(r, _)| *r == reason)?;
+    path.starts_with('/').then_some((path, status))
+}
+
+struct SqlQuery {
+    sql: String,
+    duration_ms: f64,
+    args: Option<String>,
+    alias: Option<String>,
+}
+
+/// `(secs) SQL; args=ARGS; alias=ALIAS`; `alias=` was added in Django 3.2
+/// and `args=` may be missing for statements logged by the schema editor.
+fn parse_sql(message: &str) -> Option<SqlQuery> {
+    let (secs, rest) = sql_duration(message)?;
+
+    let (rest, alias) = match rest.rsplit_once("; alias=") {
+        Some((rest, alias)) => (rest, Some(alias.to_string())),
+        None => (rest, None),
+    };
+    let (sql, args) = match rest.rsplit_once("; args=") {
+        Some((sql, args)) => (sql, Some(args.to_string())),
+        None => (rest, None),
+    };
+    Some(SqlQuery {
+        sql: sql.to_string(),
+        duration_ms: secs * 1000.0,
+        args,
+        alias,
+    })
+}
+
+/// `(0.002) SELECT ...`: the duration in seconds and the rest of the message.
+fn sql_duration(message: &str) -> Option<(f64, &str)> {
+    let (secs, rest) = message.strip_prefix('(')?.split_once(") ")?;
+    Some((secs.parse().ok()?, rest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::FieldValue;
+
+    const SAMPLE: &str = r#"Watching for file changes with StatReloader
+[31/Aug/2025 22:50:01] "GET /api/users HTTP/1.1" 200 1234
+[31/Aug/2025 22:50:02] "GET /missing HTTP/1.1" 404 179
+(0.002) SELECT "auth_user"."id" FROM "auth_user" WHERE "auth_user"."id" = 1; args=(1,); alias=default
+ERROR 2025-08-31 22:50:03,120 django.request Internal Server Error: /api/orders
+Traceback (most recent call last):
+  File "/app/orders/views.py", line 12, in create
+    order.save()
+django.db.utils.IntegrityError: duplicate key
+"#;
+
+    #[test]
+    fn parse_sample() {
+        let v = DjangoParser.parse(SAMPLE).unwrap();
+        assert_eq!(v.len(), 4);
+
+        assert_eq!(v[0].timestamp.as_deref(), Some("31/Aug/2025 22:50:01"));
+        assert_eq!(v[0].service.as_deref(), Some("django.server"));
+        assert_eq!(
+            v[0].message.as_deref(),
+            Some(r#""GET /api/users HTTP/1.1" 200 1234"#)
+        );
+        let http = v[0].http.as_ref().unwrap();
+        assert_eq!(http.method.as_deref(), Some("GET"));
+        assert_eq!(http.path.as_deref(), Some("/api/users"));
+        assert_eq!((http.status, http.bytes), (Some(200), Some(1234)));
+        assert_eq!(v[1].level, Some(Severity::Warn));
+
+        assert_eq!(v[2].service.as_deref(), Some("django.db.backends"));
+        assert_eq!(
+            v[2].fields.get("sql_duration_ms"),
+            Some(&FieldValue::Float(2.0))
+        );
+        assert_eq!(v[2].fields.get("sql_args"), Some(&FieldValue::from("(1,)")));
+        assert_eq!(
+            v[2].fields.get("db_alias"),
+            Some(&FieldValue::from("default"))
+        );
+        assert!(matches!(
+            v[2].fields.get("sql"),
+            Some(FieldValue::String(sql)) if sql.starts_with("SELECT") && sql.ends_with("= 1")
+        ));
+
+        assert_eq!(v[3].service.as_deref(), Some("django.request"));
+        assert_eq!(v[3].level, Some(Severity::Error));
+        let http = v[3].http.as_ref().unwrap();
+        assert_eq!(
+            (http.path.as_deref(), http.status),
+            (Some("/api/orders"), Some(500))
+        );
+        let Some(FieldValue::Map(exception)) = v[3].fields.get("exception") else {
+            panic!("missing exception");
+        };
+        assert_eq!(
+            exception.get("type"),
+            Some(&FieldValue::from("django.db.utils.IntegrityError"))
+        );
+    }
+
+    #[test]
+    fn start_check_agrees_with_parse_line() {
+        let near_misses = [
+            "\x1b[31m[31/Aug/2025 22:50:05] \"GET / HTTP/1.1\" 500 145\x1b[0m",
+            "Not Found:",
+            "Not Found: favicon.ico",
+            "(slow) query",
+            "(1abc) SELECT 1",
+            "[31/Aug/2025 22:50:05] \"GET / HTTP/1.1\" 5000 145",
+            "[31/Aug/2025 22:50:05] GET / HTTP/1.1",
+            "\"GET / HTTP/1.1\" ok",
+        ];
+        for line in SAMPLE.lines().chain(near_misses) {
+            assert_eq!(
+                is_start(line.as_bytes()),
+                parse_line(line.as_bytes()).is_some(),
+                "{:?}",
+                line
+            );
+        }
+    }
+}
@@ -0,0 +1,275 @@
+//! Flask logs: Werkzeug development server request lines, Flask's default
+//! `[asctime] LEVEL in module: message` handler, and `logging.basicConfig`
+//! style `LEVEL:logger:message` lines. Tracebacks (e.g. after
+//! `Exception on /path [GET]`) are folded into the entry above them.
+
+use crate::config::{HttpRequest, LogEntry};
+use crate::parsers::LogParser;
+use crate::parsers::access::{dev_server_request, is_dev_server_request, strip_ansi};
+use crate::parsers::multiline::Multiline;
+use crate::parsers::python_web;
+use crate::severity::Severity;
+use std::sync::LazyLock;
+
+const LOGGER_WERKZEUG: &str = "werkzeug";
+
+static MULTILINE: LazyLock<Multiline> = LazyLock::new(|| Multiline::new().start_with(is_start));
+
+pub struct FlaskParser;
+
+impl LogParser for FlaskParser {
+    fn name(&self) -> &str {
+        "flask"
+    }
+
+    fn aliases(&self) -> &[&str] {
+        &["werkzeug"]
+    }
+
+    fn description(&self) -> &str {
+        "Flask app logs and Werkzeug development server requests"
+    }
+
+    fn parse_line(&self, line: &[u8]) -> Option<LogEntry> {
+        parse_line(line)
+    }
+
+    fn multiline(&self) -> Option<&Multiline> {
+        Some(&MULTILINE)
+    }
+
+    fn parse_record(&self, first: &[u8], continuation: &[&[u8]]) -> Option<LogEntry> {
+        let entry = parse_line(first)?;
+        Some(python_web::attach_continuation(entry, continuation))
+    }
+}
+
+/// Whether `line` is a header `parse_line` accepts, matched without building
+/// the entry so grouping a record doesn't parse its first line twice.
+fn is_start(line: &[u8]) -> bool {
+    let Ok(line) = std::str::from_utf8(line) else {
+        return false;
+    };
+    header(strip_ansi(line).trim_end()).is_some()
+}
+
+/// The shapes of a record's first line.
+enum Header<'a> {
+    /// `[2025-08-31 22:50:01,234] ERROR in app: message`
+    Handler {
+        timestamp: &'a str,
+        level: Severity,
+        module: &'a str,
+        message: &'a str,
+    },
+    /// ` * Running on http://127.0.0.1:5000`
+    Banner(&'a str),
+    /// `INFO:werkzeug:message`
+    Logger {
+        level: Severity,
+        logger: &'a str,
+        message: &'a str,
+    },
+    /// A request or `Exception on` message without a header.
+    Bare(&'a str),
+}
+
+fn header(line: &str) -> Option<Header<'_>> {
+    if let Some(rest) = line.strip_prefix('[')
+        && let Some((timestamp, rest)) = rest.split_once("] ")
+        && timestamp.as_bytes().get(4) == Some(&b'-')
+    {
+        let (level, rest) = rest.split_once(" in ")?;
+        let (module, message) = rest.split_once(": ")?;
+        return Some(Header::Handler {
+            timestamp,
+            level: Severity::from_python(level)?,
+            module,
+            message,
+        });
+    }
+
+    if let Some(banner) = line.strip_prefix(" * ") {
+        return Some(Header::Banner(banner));
+    }
+
+    if let Some((level, rest)) = line.split_once(':')
+        && let Some(level) = Severity::from_python(level)
+        && let Some((logger, message)) = rest.split_once(':')
+        && !logger.contains(' ')
+    {
+        return Some(Header::Logger {
+            level,
+            logger,
+            message,
+        });
+    }
+
+    (werkzeug_request(line).is_some() || exception_on(line).is_some()).then_some(Header::Bare(line))
+}
+
+fn parse_line(line: &[u8]) -> Option<LogEntry> {
+    let line = std::str::from_utf8(line).ok()?;
+    let line = strip_ansi(line);
+
+    let mut entry = match header(line.trim_end())? {
+        Header::Handler {
+            timestamp,
+            level,
+            module,
+            message,
+        } => LogEntry {
+            timestamp: Some(timestamp.to_string()),
+            service: Some(module.to_string()),
+            level: Some(level),
+            message: Some(message.to_string()),
+            ..Default::default()
+        },
+        Header::Banner(banner) => {
+            return Some(LogEntry {
+                service: Some(LOGGER_WERKZEUG.to_string()),
+                level: Some(Severity::Info),
+                message: Some(banner.to_string()),
+                ..Default::default()
+            });
+        }
+        Header::Logger {
+            level,
+            logger,
+            message,
+        } => LogEntry {
+            service: Some(logger.to_string()),
+            level: Some(level),
+            message: Some(message.to_string()),
+            ..Default::default()
+        },
+        Header::Bare(message) => LogEntry {
+            message: Some(message.to_string()),
+            ..Default::default()
+        },
+    };
+    enrich(&mut entry);
+    Some(entry)
+}
+
+/// `127.0.0.1 - - [31/Aug/2025 22:50:01] "GET / HTTP/1.1" 200 -`: the
+/// client address and the request after it.
+fn werkzeug_request(message: &str) -> Option<(&str, &str)> {
+    let (addr, rest) = message.split_once(" - - ")?;
+    (!addr.is_empty() && !addr.contains(' ') && is_dev_server_request(rest)).then_some((addr, rest))
+}
+
+/// `Exception on /api/users [POST]`: the path and method.
+fn exception_on(message: &str) -> Option<(&str, &str)> {
+    let (path, method) = message.strip_prefix("Exception on ")?.rsplit_once(" [")?;
+    Some((path, method.strip_suffix(']')?))
+}
+
+/// Recognize Werkzeug request lines and Flask's `Exception on PATH [METHOD]`.
+fn enrich(entry: &mut LogEntry) {
+    let Some(message) = entry.message.as_deref() else {
+        return;
+    };
+
+    if let Some((addr, rest)) = werkzeug_request(message)
+        && let Some((timestamp, http)) = dev_server_request(rest)
+    {
+        let status = http.status.unwrap_or_default();
+        entry.host = Some(addr.to_string());
+        entry.timestamp = Some(timestamp.to_string());
+        entry.service = Some(LOGGER_WERKZEUG.to_string());
+        entry.level = Some(Severity::from_http_status(status));
+        entry.message = Some(rest[timestamp.len() + 3..].to_string());
+        entry.http = Some(http);
+        return;
+    }
+
+    if let Some((path, method)) = exception_on(message) {
+        let http = HttpRequest {
+            method: Some(method.to_string()),
+            path: Some(path.to_string()),
+            status: Some(500),
+            ..Default::default()
+        };
+        entry.level.get_or_insert(Severity::Error);
+        entry.http = Some(http);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::FieldValue;
+
+    const SAMPLE: &str = " * Running on http://127.0.0.1:5000
+127.0.0.1 - - [31/Aug/2025 22:50:01] \"GET /api/users?page=2 HTTP/1.1\" 200 -
+INFO:werkzeug:10.0.0.7 - - [31/Aug/2025 22:50:02] \"\x1b[33mPOST /login HTTP/1.1\" 404 -\x1b[0m
+[2025-08-31 22:50:03,120] ERROR in app: Exception on /api/orders [POST]
+Traceback (most recent call last):
+  File \"/app/app.py\", line 20, in create_order
+    raise KeyError(\"sku\")
+KeyError: 'sku'
+WARNING:app.billing:card declined
+";
+
+    #[test]
+    fn parse_sample() {
+        let v = FlaskParser.parse(SAMPLE).unwrap();
+        assert_eq!(v.len(), 5);
+
+        assert_eq!(
+            v[0].message.as_deref(),
+            Some("Running on http://127.0.0.1:5000")
+        );
+
+        assert_eq!(v[1].host.as_deref(), Some("127.0.0.1"));
+        assert_eq!(v[1].timestamp.as_deref(), Some("31/Aug/2025 22:50:01"));
+        assert_eq!(v[1].service.as_deref(), Some("werkzeug"));
+        let http = v[1].http.as_ref().unwrap();
+        assert_eq!(http.path.as_deref(), Some("/api/users?page=2"));
+        assert_eq!(http.status, Some(200));
+
+        assert_eq!(v[2].host.as_deref(), Some("10.0.0.7"));
+        assert_eq!(v[2].level, Some(Severity::Warn));
+        assert_eq!(v[2].http.as_ref().unwrap().method.as_deref(), Some("POST"));
+
+        assert_eq!(v[3].service.as_deref(), Some("app"));
+        assert_eq!(v[3].level, Some(Severity::Error));
+        let http = v[3].http.as_ref().unwrap();
+        assert_eq!(
+            (http.method.as_deref(), http.path.as_deref()),
+            (Some("POST"), Some("/api/orders"))
+        );
+        let Some(FieldValue::Map(exception)) = v[3].fields.get("exception") else {
+            panic!("missing exception");
+        };
+        assert_eq!(exception.get("type"), Some(&FieldValue::from("KeyError")));
+
+        assert_eq!(v[4].service.as_deref(), Some("app.billing"));
+        assert_eq!(v[4].message.as_deref(), Some("card declined"));
+    }
+
+    #[test]
+    fn start_check_agrees_with_parse_line() {
+        let near_misses = [
+            "\x1b[31mERROR:app:boom\x1b[0m",
+            "Exception on /x [GET]",
+            "Exception on /x GET",
+            " - - [x",
+            "[2025-08-31 x] garbage",
+            "[2025-08-31 22:50:03,120] LOUD in app: unknown level",
+            "[2025-08-31 22:50:03,120] ERROR app: no module",
+            "ERROR: something",
+            "ERROR:two words:message",
+            "127.0.0.1 - - [31/Aug/2025 22:50:01] not a request",
+        ];
+        for line in SAMPLE.lines().chain(near_misses) {
+            assert_eq!(
+                is_start(line.as_bytes()),
+                parse_line(line.as_bytes()).is_some(),
+                "{:?}",
+                line
+            );
+        }
+    }
+}
@@ -0,0 +1,342 @@
+//! Fallback for application logs laid out as `timestamp level message`:
+//!
+//! ```text
+//! 2025-08-31T22:50:01.234Z INFO [worker] job 17 done
+//! [2025-08-31 22:50:01,234] [ERROR] connection reset
+//! 1756680601.234 warn disk almost full
+//! 2025-08-31 22:50:01.234 | DEBUG    | cache miss
+//! ```
+//!
+//! The timestamp may be ISO 8601, slash-separated, CLF, BSD syslog or Unix
+//! epoch (seconds or millis), optionally in brackets. The level may be bare,
+//! bracketed or followed by a colon, and a `[component]` before or after it
+//! becomes the service. Lines without a leading timestamp continue the
+//! previous record.
+
+use crate::config::LogEntry;
+use crate::parsers::LogParser;
+use crate::parsers::multiline::Multiline;
+use crate::severity::Severity;
+use memchr::memchr;
+use std::sync::LazyLock;
+
+static MULTILINE: LazyLock<Multiline> =
+    LazyLock::new(|| Multiline::new().start_with(|line| timestamp(line).is_some()));
+
+pub struct GenericParser;
+
+impl LogParser for GenericParser {
+    fn name(&self) -> &str {
+        "generic"
+    }
+
+    fn description(&self) -> &str {
+        "Any `timestamp level message` application log"
+    }
+
+    fn parse_line(&self, line: &[u8]) -> Option<LogEntry> {
+        parse_line(line)
+    }
+
+    fn multiline(&self) -> Option<&Multiline> {
+        Some(&MULTILINE)
+    }
+
+    /// Only claim lines that have both a timestamp and a level, so the
+    /// fallback doesn't outscore formats it happens to overlap.
+    fn sniff(&self, line: &[u8]) -> bool {
+        parse_line(line).is_some_and(|entry| entry.level.is_some())
+    }
+}
+
+fn parse_line(line: &[u8]) -> Option<LogEntry> {
+    let (ts, mut pos) = timestamp(line)?;
+    let line = std::str::from_utf8(line).ok()?;
+
+    let mut entry = LogEntry {
+        timestamp: Some(line[ts].to_string()),
+        ..Default::default()
+    };
+
+    // level and [component], in either order
+    for _ in 0..2 {
+        pos = skip_separators(line, pos);
+        let rest = &line[pos..];
+        let token_len = if rest.starts_with(['[', '<']) {
+            rest.find([']', '>']).map_or(0, |end| end + 1)
+        } else {
+            rest.find(' ').unwrap_or(rest.len())
+        };
+        let token = &rest[..token_len];
+
+        if entry.level.is_none()
+            && let Some(level) = level(token)
+        {
+            entry.level = Some(level);
+        } else if entry.service.is_none()
+            && token.len() > 2
+            && let Some(component) = token.strip_prefix('[').and_then(|t| t.strip_suffix(']'))
+        {
+            entry.service = Some(component.to_string());
+        } else {
+            break;
+        }
+        pos += token_len;
+    }
+
+    pos = skip_separators(line, pos);
+    entry.message = Some(line[pos..].trim_end().to_string());
+    Some(entry)
+}
+
+fn skip_separators(line: &str, pos: usize) -> usize {
+    let rest = &line[pos..];
+    pos + rest.len() - rest.trim_start_matches([' ', '\t', '|']).len()
+}
+
+/// `INFO`, `[warn]`, `<error>`, `ERROR:`, per [`Severity`]'s `FromStr`.
+fn level(token: &str) -> Option<Severity> {
+    let name = token
+        .strip_prefix(['[', '<'])
+        .and_then(|t| t.strip_suffix([']', '>']))
+        .unwrap_or_else(|| token.strip_suffix(':').unwrap_or(token))
+        .trim();
+    if name.is_empty() || name.len() > 13 {
+        return None;
+    }
+    name.parse().ok()
+}
+
+/// Range of the timestamp at the start of `line` (without brackets) and the
+/// offset just past it.
+fn timestamp(line: &[u8]) -> Option<(std::ops::Range<usize>, usize)> {
+    if line.first() == Some(&b'[') {
+        let end = memchr(b']', line)?;
+        return (timestamp_len(&line[1..end]) == Some(end - 1)).then_some((1..end, end + 1));
+    }
+    let len = timestamp_len(line)?;
+    matches!(line.get(len), None | Some(b' ' | b'\t')).then_some((0..len, len))
+}
+
+fn timestamp_len(b: &[u8]) -> Option<usize> {
+    if b.first().is_some_and(u8::is_ascii_uppercase) {
+        return bsd_len(b);
+    }
+    if digits(b, 4) && matches!(b.get(4), Some(b'-' | b'/')) {
+        return iso_len(b);
+    }
+    if digits(b, 2) && b.get(2) == Some(&b'/') {
+        return clf_len(b);
+    }
+    epoch_len(b)
+}
+
+/// `Aug 31 22:50:01` / `Aug  1 22:50:01`.
+fn bsd_len(b: &[u8]) -> Option<usize> {
+    let head = b.get(..7)?;
+    if !(head[1..3].iter().all(u8::is_ascii_lowercase)
+        && head[3] == b' '
+        && (head[4] == b' ' || head[4].is_ascii_digit())
+        && head[5].is_ascii_digit()
+        && head[6] == b' ')
+    {
+        return None;
+    }
+    Some(7 + time_len(&b[7..])?)
+}
+
+/// `2025-08-31`, `2025/08/31`, optionally with `[T ]hh:mm:ss[.fff][zone]`.
+fn iso_len(b: &[u8]) -> Option<usize> {
+    let sep = b[4];
+    if !(digits(&b[5..], 2) && b.get(7) == Some(&sep) && digits(&b[8..], 2)) {
+        return None;
+    }
+    if matches!(b.get(10), Some(b'T' | b' '))
+        && let Some(time) = time_len(&b[11..])
+    {
+        let mut len = 11 + time;
+        match b.get(len) {
+            Some(b'Z') => len += 1,
+            Some(b'+' | b'-') if digits(&b[len + 1..], 2) => {
+                len += 3;
+                if b.get(len) == Some(&b':') {
+                    len += 1;
+                }
+                if digits(&b[len..], 2) {
+                    len += 2;
+                }
+            }
+            _ => {}
+        }
+        return Some(len);
+    }
+    Some(10)
+}
+
+/// `12/May/2025:06:25:24 +0000` or `12/May/2025 06:25:24`.
+fn clf_len(b: &[u8]) -> Option<usize> {
+    let month = b.get(3..6)?;
+    if !(month[0].is_ascii_uppercase()
+        && month[1..].iter().all(u8::is_ascii_lowercase)
+        && b.get(6) == Some(&b'/')
+        && digits(&b[7..], 4)
+        && matches!(b.get(11), Some(b':' | b' ')))
+    {
+        return None;
+    }
+    let mut len = 12 + time_len(&b[12..])?;
+    if b.get(len) == Some(&b' ')
+        && matches!(b.get(len + 1), Some(b'+' | b'-'))
+        && digits(&b[len + 2..], 4)
+    {
+        len += 6;
+    }
+    Some(len)
+}
+
+/// `hh:mm:ss` with an optional `.fff` / `,fff` fraction.
+fn time_len(b: &[u8]) -> Option<usize> {
+    if !(digits(b, 2)
+        && b.get(2) == Some(&b':')
+        && digits(&b[3..], 2)
+        && b.get(5) == Some(&b':')
+        && digits(&b[6..], 2))
+    {
+        return None;
+    }
+    let mut len = 8;
+    if matches!(b.get(8), Some(b'.' | b',')) {
+        let frac = b[9..].iter().take_while(|c| c.is_ascii_digit()).count();
+        if frac > 0 {
+            len += 1 + frac;
+        }
+    }
+    Some(len)
+}
+
+/// Epoch seconds (`1756680601`, `1756680601.234`) or millis (13 digits).
+fn epoch_len(b: &[u8]) -> Option<usize> {
+    let whole = b.iter().take_while(|c| c.is_ascii_digit()).count();
+    match whole {
+        10 if b.get(10) == Some(&b'.') => {
+            let frac = b[11..].iter().take_while(|c| c.is_ascii_digit()).count();
+            Some(if frac > 0 { 11 + frac } else { 10 })
+        }
+        10 | 13 => Some(whole),
+        _ => None,
+    }
+}
+
+fn digits(b: &[u8], n: usize) -> bool {
+    b.len() >= n && b[..n].iter().all(u8::is_ascii_digit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_iso_timestamp_level_and_component() {
+        let entry = GenericParser
+            .parse_line(b"2025-08-31T22:50:01.234Z INFO [worker] job 17 done")
+            .unwrap();
+        assert_eq!(entry.timestamp.as_deref(), Some("2025-08-31T22:50:01.234Z"));
+        assert_eq!(entry.level, Some(Severity::Info));
+        assert_eq!(entry.service.as_deref(), Some("worker"));
+        assert_eq!(entry.message.as_deref(), Some("job 17 done"));
+    }
+
+    #[test]
+    fn reads_bracketed_timestamp_and_level() {
+        let entry = GenericParser
+            .parse_line(b"[2025-08-31 22:50:02,100] [ERROR] connection reset")
+            .unwrap();
+        assert_eq!(entry.timestamp.as_deref(), Some("2025-08-31 22:50:02,100"));
+        assert_eq!(entry.level, Some(Severity::Error));
+        assert_eq!(entry.message.as_deref(), Some("connection reset"));
+    }
+
+    #[test]
+    fn reads_epoch_timestamp_and_colon_level() {
+        let entry = GenericParser
+            .parse_line(b"1756680603.5 warn: disk almost full")
+            .unwrap();
+        assert_eq!(entry.timestamp.as_deref(), Some("1756680603.5"));
+        assert_eq!(entry.level, Some(Severity::Warn));
+        assert_eq!(entry.message.as_deref(), Some("disk almost full"));
+    }
+
+    #[test]
+    fn skips_pipe_separators() {
+        let entry = GenericParser
+            .parse_line(b"2025-08-31 22:50:04.000 | DEBUG    | cache miss")
+            .unwrap();
+        assert_eq!(entry.level, Some(Severity::Debug));
+        assert_eq!(entry.message.as_deref(), Some("cache miss"));
+    }
+
+    #[test]
+    fn reads_component_before_level() {
+        let entry = GenericParser
+            .parse_line(b"12/May/2025:06:25:24 +0000 [db] NOTICE vacuum finished")
+            .unwrap();
+        assert_eq!(
+            entry.timestamp.as_deref(),
+            Some("12/May/2025:06:25:24 +0000")
+        );
+        assert_eq!(entry.service.as_deref(), Some("db"));
+        assert_eq!(entry.level, Some(Severity::Notice));
+    }
+
+    #[test]
+    fn keeps_lines_without_a_level() {
+        let entry = GenericParser
+            .parse_line(b"2025/08/31 22:50:05 no level here")
+            .unwrap();
+        assert_eq!(entry.level, None);
+        assert_eq!(entry.message.as_deref(), Some("no level here"));
+    }
+
+    #[test]
+    fn folds_lines_without_a_timestamp() {
+        let input = "[2025-08-31 22:50:02,100] [ERROR] connection reset
+java.io.IOException: reset by peer
+\tat Conn.read(Conn.java:42)
+1756680603.5 warn: disk almost full
+";
+        let v = GenericParser.parse(input).unwrap();
+        assert_eq!(v.len(), 2);
+        assert_eq!(
+            v[0].message.as_deref(),
+            Some(
+                "connection reset\njava.io.IOException: reset by peer\n\tat Conn.read(Conn.java:42)"
+            )
+        );
+    }
+
+    #[test]
+    fn sniff_needs_a_timestamp_and_a_level() {
+        assert!(GenericParser.sniff(b"Aug 31 22:50:01 ERROR [api] boom"));
+        assert!(!GenericParser.sniff(b"2025/08/31 22:50:05 no level here"));
+        assert!(!GenericParser.sniff(b"ERROR no timestamp"));
+    }
+
+    #[test]
+    fn rejects_lines_without_a_timestamp() {
+        assert!(GenericParser.parse_line(b"job 17 done").is_none());
+        assert!(
+            GenericParser
+                .parse_line(b"[2025-08-31 22:50:02,100")
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_utf8() {
+        assert!(
+            GenericParser
+                .parse_line(b"2025-08-31T22:50:01Z INFO caf\xff")
+                .is_none()
+        );
+    }
+}
@@ -0,0 +1,448 @@
+//! Grok patterns (`%{IPORHOST:client} \[%{HTTPDATE:timestamp}\] %{GREEDYDATA:message}`),
+//! for in-house formats that don't warrant a parser of their own.
+//!
+//! `%{NAME}` expands to the named pattern from the library, `%{NAME:field}`
+//! also captures it and `%{NAME:field:int}` / `:float` types the value.
+//! Captures named `timestamp`, `level`, `message`, `host` or `service` fill
+//! the entry; any other capture is kept in `fields` (`[a][b]` is named
+//! `a.b`). Plain regex named groups (`(?<field>...)`) are captured as text.
+//!
+//! The library starts with the standard patterns in [`BUILTIN_PATTERNS`];
+//! pattern files in the same `NAME regex` layout can be added on top.
+
+use crate::config::{FieldValue, LogEntry};
+use crate::parsers::LogParser;
+use crate::severity::Severity;
+use anyhow::{Context, Result, anyhow, bail};
+use regex::Regex;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::LazyLock;
+
+/// The bundled pattern library: one `NAME regex` definition per line,
+/// `#` comments. Covers the common Logstash base patterns; constructs the
+/// `regex` crate doesn't support (look-around, atomic groups) are relaxed.
+pub const BUILTIN_PATTERNS: &str = r#"
+USERNAME [a-zA-Z0-9._-]+
+USER %{USERNAME}
+EMAILLOCALPART [a-zA-Z0-9!#$%&'*+/=?^_`{|}~-]+(?:\.[a-zA-Z0-9!#$%&'*+/=?^_`{|}~-]+)*
+EMAILADDRESS %{EMAILLOCALPART}@%{HOSTNAME}
+INT (?:[+-]?(?:[0-9]+))
+BASE10NUM (?:[+-]?(?:[0-9]+(?:\.[0-9]+)?|\.[0-9]+))
+NUMBER (?:%{BASE10NUM})
+BASE16NUM (?:0[xX])?[0-9a-fA-F]+
+POSINT \b(?:[1-9][0-9]*)\b
+NONNEGINT \b(?:[0-9]+)\b
+WORD \b\w+\b
+NOTSPACE \S+
+SPACE \s*
+DATA .*?
+GREEDYDATA .*
+QUOTEDSTRING (?:"(?:[^"\\]|\\.)*"|'(?:[^'\\]|\\.)*'|`(?:[^`\\]|\\.)*`)
+QS %{QUOTEDSTRING}
+UUID [A-Fa-f0-9]{8}-(?:[A-Fa-f0-9]{4}-){3}[A-Fa-f0-9]{12}
+
+# Networking
+MAC (?:%{CISCOMAC}|%{WINDOWSMAC}|%{COMMONMAC})
+CISCOMAC (?:(?:[A-Fa-f0-9]{4}\.){2}[A-Fa-f0-9]{4})
+WINDOWSMAC (?:(?:[A-Fa-f0-9]{2}-){5}[A-Fa-f0-9]{2})
+COMMONMAC (?:(?:[A-Fa-f0-9]{2}:){5}[A-Fa-f0-9]{2})
+IPV4 (?:(?:25[0-5]|2[0-4][0-9]|1[0-9]{2}|[1-9]?[0-9])\.){3}(?:25[0-5]|2[0-4][0-9]|1[0-9]{2}|[1-9]?[0-9])
+IPV6 (?:[0-9A-Fa-f]{1,4}:){7}[0-9A-Fa-f]{1,4}|(?:[0-9A-Fa-f]{1,4}:){1,7}:(?:%{IPV4})?|(?:[0-9A-Fa-f]{1,4}:){0,6}(?::[0-9A-Fa-f]{1,4}){1,7}|::(?:ffff:)?%{IPV4}|::
+IP (?:%{IPV6}|%{IPV4})
+HOSTNAME \b(?:[0-9A-Za-z][0-9A-Za-z-]{0,62})(?:\.(?:[0-9A-Za-z][0-9A-Za-z-]{0,62}))*\.?
+IPORHOST (?:%{IP}|%{HOSTNAME})
+HOSTPORT %{IPORHOST}:%{POSINT}
+
+# Paths and URIs
+PATH (?:%{UNIXPATH}|%{WINPATH})
+UNIXPATH (?:/[^/\s]*)+
+WINPATH (?:[A-Za-z]+:|\\)(?:\\[^\\?*\s]*)+
+URIPROTO [A-Za-z][A-Za-z0-9+\-.]+
+URIHOST %{IPORHOST}(?::%{POSINT})?
+URIPATH (?:/[A-Za-z0-9$.+!*'(){},~:;=@#%&_\-]*)+
+URIPARAM \?[A-Za-z0-9$.+!*'|(){},~@#%&/=:;_?\-\[\]<>]*
+URIPATHPARAM %{URIPATH}(?:%{URIPARAM})?
+URI %{URIPROTO}://(?:%{USER}(?::[^@]*)?@)?(?:%{URIHOST})?(?:%{URIPATHPARAM})?
+
+# Dates and times
+MONTH \b(?:[Jj]an(?:uary)?|[Ff]eb(?:ruary)?|[Mm]ar(?:ch)?|[Aa]pr(?:il)?|[Mm]ay|[Jj]un(?:e)?|[Jj]ul(?:y)?|[Aa]ug(?:ust)?|[Ss]ep(?:tember)?|[Oo]ct(?:ober)?|[Nn]ov(?:ember)?|[Dd]ec(?:ember)?)\b
+MONTHNUM (?:0?[1-9]|1[0-2])
+MONTHDAY (?:0[1-9]|[12][0-9]|3[01]|[1-9])
+DAY (?:Mon(?:day)?|Tue(?:sday)?|Wed(?:nesday)?|Thu(?:rsday)?|Fri(?:day)?|Sat(?:urday)?|Sun(?:day)?)
+YEAR (?:\d\d){1,2}
+HOUR (?:2[0123]|[01]?[0-9])
+MINUTE (?:[0-5][0-9])
+SECOND (?:(?:[0-5]?[0-9]|60)(?:[:.,][0-9]+)?)
+TIME %{HOUR}:%{MINUTE}(?::%{SECOND})?
+DATE_US %{MONTHNUM}[/-]%{MONTHDAY}[/-]%{YEAR}
+DATE_EU %{MONTHDAY}[./-]%{MONTHNUM}[./-]%{YEAR}
+DATE (?:%{DATE_US}|%{DATE_EU})
+DATESTAMP %{DATE}[- ]%{TIME}
+TZ (?:[APMCE][SD]T|UTC)
+ISO8601_TIMEZONE (?:Z|[+-]%{HOUR}(?::?%{MINUTE}))
+TIMESTAMP_ISO8601 %{YEAR}-%{MONTHNUM}-%{MONTHDAY}[T ]%{HOUR}:?%{MINUTE}(?::?%{SECOND})?%{ISO8601_TIMEZONE}?
+HTTPDATE %{MONTHDAY}/%{MONTH}/%{YEAR}:%{TIME} %{INT}
+SYSLOGTIMESTAMP %{MONTH} +%{MONTHDAY} %{TIME}
+DATESTAMP_EVENTLOG %{YEAR}%{MONTHNUM}%{MONTHDAY}%{HOUR}%{MINUTE}%{SECOND}
+
+# Log levels
+LOGLEVEL (?:[Aa]lert|ALERT|[Tt]race|TRACE|[Dd]ebug|DEBUG|[Nn]otice|NOTICE|[Ii]nfo(?:rmation)?|INFO(?:RMATION)?|[Ww]arn(?:ing)?|WARN(?:ING)?|[Ee]rr(?:or)?|ERR(?:OR)?|[Cc]rit(?:ical)?|CRIT(?:ICAL)?|[Ff]atal|FATAL|[Ss]evere|SEVERE|[Ee]merg(?:ency)?|EMERG(?:ENCY)?)
+
+# Syslog
+PROG [\x21-\x5a\x5c\x5e-\x7e]+
+SYSLOGPROG %{PROG:program}(?:\[%{POSINT:pid:int}\])?
+SYSLOGHOST %{IPORHOST}
+SYSLOGFACILITY <%{NONNEGINT:facility:int}.%{NONNEGINT:priority:int}>
+SYSLOGBASE %{SYSLOGTIMESTAMP:timestamp} (?:%{SYSLOGFACILITY} )?%{SYSLOGHOST:host} %{SYSLOGPROG}:
+SYSLOGLINE %{SYSLOGBASE} %{GREEDYDATA:message}
+
+# HTTP servers
+HTTPDUSER (?:%{EMAILADDRESS}|%{USER})
+COMMONAPACHELOG %{IPORHOST:host} %{HTTPDUSER:ident} %{USER:auth} \[%{HTTPDATE:timestamp}\] "(?:%{WORD:verb} %{NOTSPACE:request}(?: HTTP/%{NUMBER:httpversion})?|%{DATA:rawrequest})" %{NUMBER:response:int} (?:%{NUMBER:bytes:int}|-)
+COMBINEDAPACHELOG %{COMMONAPACHELOG} %{QS:referrer} %{QS:agent}
+"#;
+
+/// `%{NAME}`, `%{NAME:field}` or `%{NAME:field:type}`.
+static REFERENCE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"%\{(\w+)(?::([^:}]+))?(?::(int|float|string))?\}").expect("valid regex")
+});
+
+/// `(?P<name>` or `(?<name>`, a plain named group.
+static NAMED_GROUP: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\(\?P?<(\w+)>").expect("valid regex"));
+
+/// Prefix of the group names generated for `%{NAME:field}` captures and
+/// plain named groups.
+const GROUP_PREFIX: &str = "__grok";
+/// Nesting limit when expanding patterns, which also catches cycles.
+const MAX_DEPTH: usize = 32;
+
+/// A pattern library.
+#[derive(Debug, Clone)]
+pub struct Grok {
+    patterns: HashMap<String, String>,
+}
+
+impl Default for Grok {
+    /// The library with [`BUILTIN_PATTERNS`].
+    fn default() -> Self {
+        let mut grok = Self::empty();
+        grok.add_patterns(BUILTIN_PATTERNS)
+            .expect("builtin patterns are well-formed");
+        grok
+    }
+}
+
+impl Grok {
+    /// A library without any patterns.
+    pub fn empty() -> Self {
+        Self {
+            patterns: HashMap::new(),
+        }
+    }
+
+    /// Add `NAME regex` definitions, one per line; blank lines and `#`
+    /// comments are skipped. A name that is already defined is replaced.
+    pub fn add_patterns(&mut self, text: &str) -> Result<()> {
+        for (n, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((name, pattern)) = line.split_once(char::is_whitespace) else {
+                bail!("line {}: expected NAME PATTERN, got '{}'", n + 1, line);
+            };
+            if !name.bytes().all(|c| c.is_ascii_alphanumeric() || c == b'_') {
+                bail!("line {}: invalid pattern name '{}'", n + 1, name);
+            }
+            self.patterns
+                .insert(name.to_string(), pattern.trim().to_string());
+        }
+        Ok(())
+    }
+
+    /// Add the definitions in a pattern file (see [`add_patterns`](Self::add_patterns)).
+    pub fn load_file(&mut self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let text =
+            std::fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+        self.add_patterns(&text)
+            .with_context(|| format!("in {}", path.display()))
+    }
+
+    /// Expand and compile a grok expression.
+    pub fn compile(&self, pattern: &str) -> Result<GrokPattern> {
+        let mut captures = Vec::new();
+        let expanded = self.expand(pattern, 0, &mut captures)?;
+        let regex = Regex::new(&expanded)?;
+        Ok(GrokPattern { regex, captures })
+    }
+
+    fn expand(&self, pattern: &str, depth: usize, captures: &mut Vec<Capture>) -> Result<String> {
+        if depth > MAX_DEPTH {
+            bail!("patterns nest too deeply (is a pattern recursive?)");
+        }
+        let mut out = String::with_capacity(pattern.len());
+        let mut last = 0;
+        for reference in REFERENCE.captures_iter(pattern) {
+            let whole = reference.get(0).expect("group 0");
+            rename_groups(&pattern[last..whole.start()], captures, &mut out);
+            last = whole.end();
+
+            let name = &reference[1];
+            let definition = self
+                .patterns
+                .get(name)
+                .ok_or_else(|| anyhow!("unknown pattern %{{{}}}", name))?;
+            let inner = self.expand(definition, depth + 1, captures)?;
+            match reference.get(2) {
+                Some(field) => {
+                    let group = format!("{}{}", GROUP_PREFIX, captures.len());
+                    out.push_str(&format!("(?P<{}>{})", group, inner));
+                    captures.push(Capture {
+                        group,
+                        field: field_name(field.as_str()),
+                        kind: match reference.get(3).map(|t| t.as_str()) {
+                            Some("int") => Kind::Int,
+                            Some("float") => Kind::Float,
+                            _ => Kind::Text,
+                        },
+                    });
+                }
+                None => out.push_str(&format!("(?:{})", inner)),
+            }
+        }
+        rename_groups(&pattern[last..], captures, &mut out);
+        Ok(out)
+    }
+}
+
+/// Copy regex text into `out`, giving each plain named group a generated
+/// name so a definition can be referenced more than once.
+fn rename_groups(text: &str, captures: &mut Vec<Capture>, out: &mut String) {
+    let mut last = 0;
+    for group in NAMED_GROUP.captures_iter(text) {
+        let whole = group.get(0).expect("group 0");
+        let escapes = text[..whole.start()]
+            .bytes()
+            .rev()
+            .take_while(|&b| b == b'\\')
+            .count();
+        if escapes % 2 == 1 {
+            continue;
+        }
+        out.push_str(&text[last..whole.start()]);
+        last = whole.end();
+
+        let name = format!("{}{}", GROUP_PREFIX, captures.len());
+        out.push_str(&format!("(?P<{}>", name));
+        captures.push(Capture {
+            group: name,
+            field: group[1].to_string(),
+            kind: Kind::Text,
+        });
+    }
+    out.push_str(&text[last..]);
+}
+
+/// `[http][status]` as `http.status`; other names as written.
+fn field_name(name: &str) -> String {
+    match name.strip_prefix('[').and_then(|n| n.strip_suffix(']')) {
+        Some(path) => path.replace("][", "."),
+        None => name.to_string(),
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Capture {
+    group: String,
+    field: String,
+    kind: Kind,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Kind {
+    Text,
+    Int,
+    Float,
+}
+
+/// A compiled grok expression.
+#[derive(Debug, Clone)]
+pub struct GrokPattern {
+    regex: Regex,
+    captures: Vec<Capture>,
+}
+
+impl GrokPattern {
+    /// The expanded regex.
+    pub fn as_str(&self) -> &str {
+        self.regex.as_str()
+    }
+
+    /// Match `line` (anywhere, unless the expression is anchored).
+    pub fn parse_line(&self, line: &str) -> Option<LogEntry> {
+        let caps = self.regex.captures(line)?;
+        let mut entry = LogEntry::default();
+        for capture in &self.captures {
+            let Some(value) = caps.name(&capture.group) else {
+                continue;
+            };
+            let value = value.as_str();
+            if set_capture(&mut entry, &capture.field, value) {
+                continue;
+            }
+            let value = match capture.kind {
+                Kind::Int => value.parse().map(FieldValue::Int).ok(),
+                Kind::Float => value.parse().map(FieldValue::Float).ok(),
+                Kind::Text => None,
+            };
+            entry.set_field(
+                capture.field.as_str(),
+                value.unwrap_or_else(|| caps[capture.group.as_str()].into()),
+            );
+        }
+        if entry.message.is_none() {
+            entry.message = Some(line.trim_end().to_string());
+        }
+        Some(entry)
+    }
+}
+
+/// Store a capture named after a core [`LogEntry`] field (`timestamp`,
+/// `level`, `message`, `host`, `service`). Returns `false` for other names,
+/// and for a level that isn't a known severity, so the caller keeps the
+/// value in `fields`.
+pub(crate) fn set_capture(entry: &mut LogEntry, name: &str, value: &str) -> bool {
+    let slot = match name {
+        "timestamp" => &mut entry.timestamp,
+        "message" => &mut entry.message,
+        "host" => &mut entry.host,
+        "service" => &mut entry.service,
+        "level" => {
+            let level = Severity::from_python(value).or_else(|| value.parse().ok());
+            entry.level = level.or(entry.level);
+            return level.is_some();
+        }
+        _ => return false,
+    };
+    if slot.is_none() {
+        *slot = Some(value.to_string());
+    }
+    true
+}
+
+/// One or more grok expressions, tried in order, as the `grok` preset.
+pub struct GrokParser {
+    patterns: Vec<GrokPattern>,
+}
+
+impl GrokParser {
+    /// Compile `patterns` against `grok`'s library.
+    pub fn new<S: AsRef<str>>(grok: &Grok, patterns: &[S]) -> Result<Self> {
+        if patterns.is_empty() {
+            bail!("no grok pattern given");
+        }
+        let patterns = patterns
+            .iter()
+            .map(|p| grok.compile(p.as_ref()))
+            .collect::<Result<_>>()?;
+        Ok(Self { patterns })
+    }
+}
+
+impl LogParser for GrokParser {
+    fn name(&self) -> &str {
+        "grok"
+    }
+
+    fn description(&self) -> &str {
+        "Lines matching the --pattern grok expressions"
+    }
+
+    fn parse_line(&self, line: &[u8]) -> Option<LogEntry> {
+        let line = std::str::from_utf8(line).ok()?;
+        self.patterns.iter().find_map(|p| p.parse_line(line))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compiles_with_library() {
+        let mut grok = Grok::default();
+        grok.add_patterns("# in-house\nJOBID job-[0-9a-f]{6}\n")
+            .unwrap();
+        let parser = GrokParser::new(
+            &grok,
+            &[
+                r"^\[%{HTTPDATE:timestamp}\] %{IPORHOST:host} %{LOGLEVEL:level} %{JOBID:[job][id]} took %{NUMBER:took_ms:float}ms (?<detail>.*)$",
+                r"^%{SYSLOGLINE}$",
+            ],
+        )
+        .unwrap();
+
+        let entry = parser
+            .parse_line(
+                b"[12/May/2025:06:25:24 +0000] 10.0.0.7 WARN job-00ff1a took 12.5ms retrying",
+            )
+            .unwrap();
+        assert_eq!(
+            entry.timestamp.as_deref(),
+            Some("12/May/2025:06:25:24 +0000")
+        );
+        assert_eq!(entry.host.as_deref(), Some("10.0.0.7"));
+        assert_eq!(entry.level, Some(Severity::Warn));
+        assert_eq!(
+            entry.fields.get("job.id"),
+            Some(&FieldValue::from("job-00ff1a"))
+        );
+        assert_eq!(entry.fields.get("took_ms"), Some(&FieldValue::Float(12.5)));
+        assert_eq!(
+            entry.fields.get("detail"),
+            Some(&FieldValue::from("retrying"))
+        );
+
+        let entry = parser
+            .parse_line(b"Aug 31 22:50:01 web01 sshd[812]: Accepted publickey for deploy")
+            .unwrap();
+        assert_eq!(entry.timestamp.as_deref(), Some("Aug 31 22:50:01"));
+        assert_eq!(entry.host.as_deref(), Some("web01"));
+        assert_eq!(entry.fields.get("program"), Some(&FieldValue::from("sshd")));
+        assert_eq!(entry.fields.get("pid"), Some(&FieldValue::Int(812)));
+        assert_eq!(
+            entry.message.as_deref(),
+            Some("Accepted publickey for deploy")
+        );
+        assert!(parser.parse_line(b"unrelated").is_none());
+    }
+
+    #[test]
+    fn named_groups_survive_repeated_references() {
+        let mut grok = Grok::default();
+        grok.add_patterns(r"PAIR (?P<key>\w+)=\w+").unwrap();
+        let pairs = grok.compile(r"^%{PAIR} %{PAIR} \(?P<x>$").unwrap();
+        let entry = pairs.parse_line("a=1 b=2 (P<x>").unwrap();
+        assert_eq!(entry.fields.get("key"), Some(&FieldValue::from("b")));
+        assert!(!entry.fields.contains_key("x"));
+    }
+
+    #[test]
+    fn rejects_unknown_and_recursive_patterns() {
+        let mut grok = Grok::default();
+        assert!(grok.compile("%{NOPE:x}").is_err());
+        grok.add_patterns("LOOP %{LOOP}").unwrap();
+        assert!(grok.compile("%{LOOP}").is_err());
+    }
+
+    #[test]
+    fn rejects_malformed_definitions() {
+        let mut grok = Grok::default();
+        assert!(grok.add_patterns("JUSTANAME").is_err());
+        assert!(grok.add_patterns("BAD-NAME x").is_err());
+        assert!(GrokParser::new::<&str>(&grok, &[]).is_err());
+    }
+}
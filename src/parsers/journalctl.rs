@@ -1,96 +1,50 @@
 use crate::config::LogEntry;
+use crate::parsers::multiline::Multiline;
+use crate::parsers::syslog::{is_bsd_timestamp, split_tag};
+use crate::parsers::{LogParser, parse_chunked};
+use crate::severity::Severity;
 use anyhow::Result;
-use memchr::memchr_iter;
-#[cfg(feature = "parallel")]
-use rayon::prelude::*;
-use std::ops::Range;
-
-const CHUNK_BYTES: usize = 4 * 1024 * 1024; // 8MB per chunk
-const MIN_LINE_LEN: usize = 20;
+use std::sync::LazyLock;
 
 static SERVICE_JOURNAL: &str = "journalctl";
-static LEVEL_INFO: &str = "info";
-static LEVEL_WARN: &str = "warn";
-static LEVEL_ERROR: &str = "error";
+/// journalctl prints the continuation lines of a multi-line message without
+/// a timestamp.
+static MULTILINE: LazyLock<Multiline> =
+    LazyLock::new(|| Multiline::new().start_with(is_bsd_timestamp));
+const LEVEL_INFO: Severity = Severity::Info;
+const LEVEL_WARN: Severity = Severity::Warn;
+const LEVEL_ERROR: Severity = Severity::Error;
 
 pub fn parse_journal(input: &str) -> Result<Vec<LogEntry>> {
-    #[cfg(feature = "parallel")]
-    {
-        if input.len() > CHUNK_BYTES {
-            return Ok(parse_parallel(input));
-        }
-    }
-    Ok(parse_single(input.as_bytes()))
+    Ok(parse_chunked(&JournalParser, input.as_bytes()))
 }
 
-#[cfg(feature = "parallel")]
-fn parse_parallel(input: &str) -> Vec<LogEntry> {
-    let bytes = input.as_bytes();
-    let len = bytes.len();
-
-    // Build chunk ranges aligned to newline
-    let mut ranges = Vec::new();
-    let mut start = 0;
-    while start < len {
-        let mut end = (start + CHUNK_BYTES).min(len);
-        if end < len {
-            while end < len && bytes[end] != b'\n' {
-                end += 1;
-            }
-            if end < len {
-                end += 1;
-            }
-        } else {
-            end = len;
-        }
-        ranges.push(start..end);
-        start = end;
+/// Registry adapter for [`parse_journal`].
+pub struct JournalParser;
+
+impl LogParser for JournalParser {
+    fn name(&self) -> &str {
+        "journalctl"
     }
 
-    // Parse chunks in parallel
-    let parts: Vec<Vec<LogEntry>> = ranges
-        .into_par_iter()
-        .map(|r| {
-            let approx_lines = (r.end - r.start) / 80;
-            let mut out = Vec::with_capacity(approx_lines);
-            parse_chunk(&bytes[r], &mut out);
-            out
-        })
-        .collect();
-
-    // Flatten
-    let total: usize = parts.iter().map(Vec::len).sum();
-    let mut result = Vec::with_capacity(total);
-    for mut p in parts {
-        result.append(&mut p);
+    fn aliases(&self) -> &[&str] {
+        &["journal"]
     }
-    result
-}
 
-fn parse_single(bytes: &[u8]) -> Vec<LogEntry> {
-    let mut out = Vec::with_capacity(bytes.len() / 80);
-    parse_chunk(bytes, &mut out);
-    out
-}
+    fn description(&self) -> &str {
+        "journalctl short text output"
+    }
 
-fn parse_chunk(bytes: &[u8], out: &mut Vec<LogEntry>) {
-    let mut start = 0;
-    for nl in memchr_iter(b'\n', bytes) {
-        let line = &bytes[start..nl];
-        if line.len() >= MIN_LINE_LEN {
-            if let Some(entry) = parse_line(line) {
-                out.push(entry);
-            }
-        }
-        start = nl + 1;
+    fn parse_line(&self, line: &[u8]) -> Option<LogEntry> {
+        parse_line(line)
     }
-    if start < bytes.len() {
-        let line = &bytes[start..];
-        if line.len() >= MIN_LINE_LEN {
-            if let Some(entry) = parse_line(line) {
-                out.push(entry);
-            }
-        }
+
+    fn multiline(&self) -> Option<&Multiline> {
+        Some(&MULTILINE)
+    }
+
+    fn sniff(&self, line: &[u8]) -> bool {
+        is_bsd_timestamp(line) && parse_line(line).is_some()
     }
 }
 
@@ -102,7 +56,7 @@ fn parse_line(line: &[u8]) -> Option<LogEntry> {
     }
 
     // Timestamp: first 15 bytes
-    let timestamp = unsafe { std::str::from_utf8_unchecked(&line[0..15]) };
+    let timestamp = std::str::from_utf8(&line[0..15]).ok()?;
 
     // Hostname: next token
     let mut i = 16;
@@ -110,20 +64,23 @@ fn parse_line(line: &[u8]) -> Option<LogEntry> {
     while i < len && line[i] != b' ' {
         i += 1;
     }
-    let hostname = unsafe { std::str::from_utf8_unchecked(&line[host_start..i]) };
-    i += 1;
+    let hostname = std::str::from_utf8(&line[host_start..i]).ok()?;
+    i = (i + 1).min(len);
 
-    // Service: up to ':' or '['
+    // Tag: `program[pid]` up to ':'
     let svc_start = i;
-    while i < len && line[i] != b':' && line[i] != b'[' {
+    while i < len && line[i] != b':' {
+        i += 1;
+    }
+    let (service, pid) = split_tag(std::str::from_utf8(&line[svc_start..i]).ok()?);
+    i = (i + 1).min(len);
+    if i < len && line[i] == b' ' {
         i += 1;
     }
-    let service = unsafe { std::str::from_utf8_unchecked(&line[svc_start..i]) };
-    i += 1;
 
     // Remaining message
     let message = if i < len {
-        unsafe { std::str::from_utf8_unchecked(&line[i..]) }
+        std::str::from_utf8(&line[i..]).ok()?
     } else {
         ""
     };
@@ -134,42 +91,84 @@ fn parse_line(line: &[u8]) -> Option<LogEntry> {
     for &b in msg_bytes {
         match b | 0x20 {
             // lowercase ASCII
-            b'e' => {
-                if msg_bytes
-                    .windows(5)
-                    .any(|w| w.eq_ignore_ascii_case(b"error"))
-                {
-                    level = LEVEL_ERROR;
-                    break;
-                }
+            b'e' if msg_bytes
+                .windows(5)
+                .any(|w| w.eq_ignore_ascii_case(b"error")) =>
+            {
+                level = LEVEL_ERROR;
+                break;
             }
-            b'f' => {
-                if msg_bytes
-                    .windows(4)
-                    .any(|w| w.eq_ignore_ascii_case(b"fail"))
-                {
-                    level = LEVEL_ERROR;
-                    break;
-                }
+            b'f' if msg_bytes
+                .windows(4)
+                .any(|w| w.eq_ignore_ascii_case(b"fail")) =>
+            {
+                level = LEVEL_ERROR;
+                break;
             }
-            b'w' => {
-                if msg_bytes
-                    .windows(4)
-                    .any(|w| w.eq_ignore_ascii_case(b"warn"))
-                {
-                    level = LEVEL_WARN;
-                    break;
-                }
+            b'w' if msg_bytes
+                .windows(4)
+                .any(|w| w.eq_ignore_ascii_case(b"warn")) =>
+            {
+                level = LEVEL_WARN;
+                break;
             }
             _ => {}
         }
     }
 
-    Some(LogEntry {
+    let service = if service.is_empty() {
+        SERVICE_JOURNAL
+    } else {
+        service
+    };
+    let mut entry = LogEntry {
         timestamp: Some(timestamp.to_string()),
         host: Some(hostname.to_string()),
-        service: Some(SERVICE_JOURNAL.to_string()),
-        level: Some(level.to_string()),
+        service: Some(service.to_string()),
+        level: Some(level),
         message: Some(message.to_string()),
-    })
+        ..Default::default()
+    };
+    if let Some(pid) = pid {
+        entry.set_field("pid", pid);
+    }
+    Some(entry)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::FieldValue;
+
+    const SAMPLE: &str = r#"Oct 11 22:14:15 web01 systemd[1]: Started Session 42 of user root.
+Oct 11 22:14:16 web01 kernel: usb 1-1: device descriptor read failed
+Oct 11 22:14:17 web01 java[88]: Exception in thread "main" java.lang.IllegalStateException
+        at Main.main(Main.java:5)
+"#;
+
+    #[test]
+    fn parse_sample() {
+        let v = parse_journal(SAMPLE).unwrap();
+        assert_eq!(v.len(), 3);
+        assert_eq!(v[0].service.as_deref(), Some("systemd"));
+        assert_eq!(v[0].fields.get("pid"), Some(&FieldValue::Int(1)));
+        assert_eq!(
+            v[0].message.as_deref(),
+            Some("Started Session 42 of user root.")
+        );
+        assert_eq!(v[1].service.as_deref(), Some("kernel"));
+        assert!(v[1].fields.get("pid").is_none());
+        assert_eq!(
+            v[1].message.as_deref(),
+            Some("usb 1-1: device descriptor read failed")
+        );
+        assert_eq!(v[1].level, Some(Severity::Error));
+        // continuation lines are printed without a timestamp
+        assert!(
+            v[2].message
+                .as_deref()
+                .unwrap()
+                .ends_with("IllegalStateException\n        at Main.main(Main.java:5)")
+        );
+    }
 }
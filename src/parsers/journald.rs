@@ -0,0 +1,330 @@
+//! Native journald output: `journalctl -o json` (one object per line) and
+//! `journalctl -o export` (blank-line separated records, with binary fields
+//! written as `NAME\n<u64 LE length><data>\n`).
+
+use crate::config::{FieldValue, Fields, LogEntry};
+use crate::parsers::LogParser;
+use crate::severity::Severity;
+use memchr::memchr;
+use serde_json::Value;
+
+/// `journalctl -o json` / `-o json-seq` lines.
+pub struct JournalJsonParser;
+
+impl LogParser for JournalJsonParser {
+    fn name(&self) -> &str {
+        "journal_json"
+    }
+
+    fn aliases(&self) -> &[&str] {
+        &["journald_json"]
+    }
+
+    fn description(&self) -> &str {
+        "journalctl -o json (one JSON object per line)"
+    }
+
+    fn parse_line(&self, line: &[u8]) -> Option<LogEntry> {
+        // json-seq prefixes each record with an ASCII record separator
+        let line = line.strip_prefix(b"\x1e").unwrap_or(line);
+        let Ok(Value::Object(object)) = serde_json::from_slice(line) else {
+            return None;
+        };
+        if !object.contains_key("MESSAGE") && !object.contains_key("__REALTIME_TIMESTAMP") {
+            return None;
+        }
+        let mut fields = Fields::with_capacity(object.len());
+        for (key, value) in object {
+            if let Some(value) = json_value(value) {
+                fields.insert(key, value);
+            }
+        }
+        Some(build_entry(fields))
+    }
+}
+
+/// `journalctl -o export` records.
+pub struct JournalExportParser;
+
+impl LogParser for JournalExportParser {
+    fn name(&self) -> &str {
+        "journal_export"
+    }
+
+    fn aliases(&self) -> &[&str] {
+        &["journald_export"]
+    }
+
+    fn description(&self) -> &str {
+        "journalctl -o export (binary-safe journal export format)"
+    }
+
+    /// Parse one complete record (its fields, without the separating blank
+    /// line).
+    fn parse_line(&self, line: &[u8]) -> Option<LogEntry> {
+        let (fields, _) = parse_record(line)?;
+        (!fields.is_empty()).then(|| build_entry(fields))
+    }
+
+    fn parse_chunk(&self, bytes: &[u8], out: &mut Vec<LogEntry>) {
+        let mut pos = 0;
+        while pos < bytes.len() {
+            let Some((fields, used)) = parse_record(&bytes[pos..]) else {
+                // Truncated or corrupt record: resync at the next record
+                pos = self.record_start(bytes, pos + 1);
+                continue;
+            };
+            if !fields.is_empty() {
+                out.push(build_entry(fields));
+            }
+            pos += used;
+        }
+    }
+
+    /// Records end with an empty line. Binary field data may contain blank
+    /// lines too, so a boundary also has to be followed by a field name.
+    fn record_start(&self, bytes: &[u8], pos: usize) -> usize {
+        if pos == 0 {
+            return 0;
+        }
+        let mut at = pos;
+        while at < bytes.len() {
+            let from = at.saturating_sub(2);
+            let Some(sep) = memchr::memmem::find(&bytes[from..], b"\n\n") else {
+                break;
+            };
+            let start = from + sep + 2;
+            let line_end = memchr(b'\n', &bytes[start..]).map_or(bytes.len(), |i| start + i);
+            if is_field_line(&bytes[start..line_end]) {
+                return start;
+            }
+            at = start + 1;
+        }
+        bytes.len()
+    }
+
+    fn groups_lines(&self) -> bool {
+        true
+    }
+
+    fn sniff(&self, line: &[u8]) -> bool {
+        is_field_line(line)
+    }
+}
+
+/// Parse fields up to the blank line ending a record. Returns the fields and
+/// the number of bytes consumed, including the separator.
+fn parse_record(bytes: &[u8]) -> Option<(Fields, usize)> {
+    let mut fields = Fields::new();
+    let mut pos = 0;
+    while pos < bytes.len() {
+        let line_end = memchr(b'\n', &bytes[pos..]).map_or(bytes.len(), |i| pos + i);
+        let line = &bytes[pos..line_end];
+        if line.is_empty() {
+            return Some((fields, line_end + 1));
+        }
+
+        if let Some(eq) = memchr(b'=', line) {
+            push_field(&mut fields, &line[..eq], &line[eq + 1..]);
+            pos = line_end + 1;
+        } else {
+            // NAME\n, then a little-endian u64 length, the data and a newline
+            let len_start = line_end + 1;
+            let len_bytes = bytes.get(len_start..len_start + 8)?;
+            let len = u64::from_le_bytes(len_bytes.try_into().ok()?);
+            let data_start = len_start + 8;
+            let data_end = data_start.checked_add(usize::try_from(len).ok()?)?;
+            push_field(&mut fields, line, bytes.get(data_start..data_end)?);
+            pos = data_end + 1;
+        }
+    }
+    Some((fields, bytes.len()))
+}
+
+/// Add a field, turning repeated names into a list of values.
+fn push_field(fields: &mut Fields, name: &[u8], value: &[u8]) {
+    let value = FieldValue::String(String::from_utf8_lossy(value).into_owned());
+    let name = String::from_utf8_lossy(name).into_owned();
+    match fields.get_mut(&name) {
+        Some(FieldValue::List(values)) => values.push(value),
+        Some(existing) => *existing = FieldValue::List(vec![existing.clone(), value]),
+        None => {
+            fields.insert(name, value);
+        }
+    }
+}
+
+/// `NAME=value` or a bare `NAME` (binary field header), where journal field
+/// names are uppercase letters, digits and underscores.
+fn is_field_line(line: &[u8]) -> bool {
+    let name = memchr(b'=', line).map_or(line, |eq| &line[..eq]);
+    name.first()
+        .is_some_and(|b| b.is_ascii_uppercase() || *b == b'_')
+        && name
+            .iter()
+            .all(|b| b.is_ascii_uppercase() || b.is_ascii_digit() || *b == b'_')
+}
+
+/// Strings, numbers and booleans keep their type; byte arrays (how `-o json`
+/// encodes non-UTF-8 values) are decoded lossily; arrays of values (repeated
+/// fields) become lists.
+fn json_value(value: Value) -> Option<FieldValue> {
+    match value {
+        Value::Null => None,
+        Value::Bool(b) => Some(FieldValue::Bool(b)),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => Some(FieldValue::Int(i)),
+            None => Some(FieldValue::Float(n.as_f64()?)),
+        },
+        Value::String(s) => Some(FieldValue::String(s)),
+        Value::Array(items) if items.iter().all(Value::is_u64) => {
+            let bytes: Vec<u8> = items
+                .iter()
+                .filter_map(|b| b.as_u64().and_then(|b| u8::try_from(b).ok()))
+                .collect();
+            Some(FieldValue::String(
+                String::from_utf8_lossy(&bytes).into_owned(),
+            ))
+        }
+        Value::Array(items) => Some(FieldValue::List(
+            items.into_iter().filter_map(json_value).collect(),
+        )),
+        object @ Value::Object(_) => Some(FieldValue::String(object.to_string())),
+    }
+}
+
+/// Strings as-is and numbers in decimal, for the well-known fields that
+/// [`build_entry`] parses itself.
+fn scalar_string(value: FieldValue) -> Option<String> {
+    match value {
+        FieldValue::String(s) => Some(s),
+        FieldValue::Int(i) => Some(i.to_string()),
+        FieldValue::Float(f) => Some(f.to_string()),
+        FieldValue::Bool(b) => Some(b.to_string()),
+        FieldValue::List(_) | FieldValue::Map(_) => None,
+    }
+}
+
+/// Map the well-known journal fields onto [`LogEntry`]; everything else is
+/// kept in `fields` under its journal name.
+fn build_entry(mut fields: Fields) -> LogEntry {
+    let mut take = |name: &str| match fields.shift_remove(name)? {
+        FieldValue::List(values) => values.into_iter().find_map(scalar_string),
+        value => scalar_string(value),
+    };
+
+    // epoch microseconds, converted by the normalizer
+    let timestamp = take("__REALTIME_TIMESTAMP");
+    let host = take("_HOSTNAME");
+    let level = take("PRIORITY").and_then(|p| Severity::from_journald_priority(&p));
+    let message = take("MESSAGE");
+    let unit = take("_SYSTEMD_UNIT");
+    let service = take("SYSLOG_IDENTIFIER").or_else(|| unit.clone());
+    let pid = take("_PID").and_then(|pid| pid.parse::<i64>().ok());
+    let boot_id = take("_BOOT_ID");
+
+    let mut entry = LogEntry {
+        timestamp,
+        host,
+        service,
+        level,
+        message,
+        ..Default::default()
+    };
+    if let Some(pid) = pid {
+        entry.set_field("pid", pid);
+    }
+    if let Some(unit) = unit {
+        entry.set_field("unit", unit);
+    }
+    if let Some(boot_id) = boot_id {
+        entry.set_field("boot_id", boot_id);
+    }
+    entry.fields.extend(fields);
+    entry
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_json_and_export() {
+        let json = concat!(
+            r#"{"__REALTIME_TIMESTAMP":"1760220855003000","_HOSTNAME":"web01","PRIORITY":"3","#,
+            r#""_SYSTEMD_UNIT":"nginx.service","SYSLOG_IDENTIFIER":"nginx","_PID":"812","#,
+            r#""_BOOT_ID":"b1","MESSAGE":[104,105,255],"CODE_LINE":"42","TAG":["a","b"]}"#,
+            "\n{\"unrelated\":true}\n"
+        );
+        let v = JournalJsonParser.parse(json).unwrap();
+        assert_eq!(v.len(), 1);
+        assert_eq!(v[0].timestamp.as_deref(), Some("1760220855003000"));
+        assert_eq!(v[0].host.as_deref(), Some("web01"));
+        assert_eq!(v[0].service.as_deref(), Some("nginx"));
+        assert_eq!(v[0].level, Some(Severity::Error));
+        assert_eq!(v[0].message.as_deref(), Some("hi\u{fffd}"));
+        assert_eq!(v[0].fields.get("pid"), Some(&FieldValue::Int(812)));
+        assert_eq!(
+            v[0].fields.get("unit"),
+            Some(&FieldValue::from("nginx.service"))
+        );
+        assert_eq!(v[0].fields.get("boot_id"), Some(&FieldValue::from("b1")));
+        assert_eq!(v[0].fields.get("CODE_LINE"), Some(&FieldValue::from("42")));
+        assert_eq!(
+            v[0].fields.get("TAG"),
+            Some(&FieldValue::List(vec!["a".into(), "b".into()]))
+        );
+
+        let mut export = b"__REALTIME_TIMESTAMP=1760220855003000\nPRIORITY=6\nMESSAGE\n".to_vec();
+        export.extend_from_slice(&12u64.to_le_bytes());
+        export.extend_from_slice(b"line1\n\nline2\n_PID=1\nBLOB\n");
+        export.extend_from_slice(&2u64.to_le_bytes());
+        export.extend_from_slice(b"\xff\n\n\n");
+        let second = export.len();
+        export.extend_from_slice(b"MESSAGE=second\n_SYSTEMD_UNIT=cron.service\n");
+        let out = JournalExportParser.parse_bytes(&export).unwrap();
+        assert_eq!(out.len(), 2);
+        assert_eq!(
+            out[0].fields.get("BLOB"),
+            Some(&FieldValue::from("\u{fffd}\n"))
+        );
+        assert_eq!(out[0].message.as_deref(), Some("line1\n\nline2"));
+        assert_eq!(out[0].level, Some(Severity::Info));
+        assert_eq!(out[0].fields.get("pid"), Some(&FieldValue::Int(1)));
+        assert_eq!(out[1].message.as_deref(), Some("second"));
+        assert_eq!(out[1].service.as_deref(), Some("cron.service"));
+
+        // the blank line inside the binary MESSAGE is not a record boundary
+        assert_eq!(JournalExportParser.record_start(&export, 60), second);
+    }
+
+    #[test]
+    fn json_numbers_and_booleans_keep_their_type() {
+        let line = concat!(
+            r#"{"__REALTIME_TIMESTAMP":1760220855003000,"PRIORITY":4,"_PID":812,"#,
+            r#""MESSAGE":"hi","SAMPLE_RATE":0.5,"TRUNCATED":false}"#,
+        );
+        let entry = JournalJsonParser.parse_line(line.as_bytes()).unwrap();
+        assert_eq!(entry.timestamp.as_deref(), Some("1760220855003000"));
+        assert_eq!(entry.level, Some(Severity::Warn));
+        assert_eq!(entry.fields.get("pid"), Some(&FieldValue::Int(812)));
+        assert_eq!(
+            entry.fields.get("SAMPLE_RATE"),
+            Some(&FieldValue::Float(0.5))
+        );
+        assert_eq!(
+            entry.fields.get("TRUNCATED"),
+            Some(&FieldValue::Bool(false))
+        );
+    }
+
+    #[test]
+    fn export_resyncs_after_a_truncated_binary_field() {
+        let mut export = b"MESSAGE\n".to_vec();
+        export.extend_from_slice(&1000u64.to_le_bytes());
+        export.extend_from_slice(b"short\n\nMESSAGE=next\n");
+        let out = JournalExportParser.parse_bytes(&export).unwrap();
+        assert_eq!(out.len(), 1);
+        assert_eq!(out[0].message.as_deref(), Some("next"));
+    }
+}
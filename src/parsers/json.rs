@@ -1,3 +1,318 @@
+//! Structured application logs, one JSON object per line (bunyan, pino, zap,
+//! logrus, structlog, serilog compact, ...).
+//!
+//! Core fields are looked up under configurable keys ([`JsonKeys`]); every
+//! other key is kept in `fields`, with nested objects flattened into dotted
+//! names (`{"http":{"status":200}}` becomes `http.status`).
+
+use crate::config::{FieldValue, Fields, LogEntry};
+use crate::parsers::LogParser;
+use crate::severity::Severity;
+use anyhow::{Result, bail};
+use serde_json::Value;
+
+/// Parse one line as a JSON value.
 pub fn parse(line: &str) -> Option<serde_json::Value> {
     serde_json::from_str(line).ok()
 }
+
+/// Keys tried, in order, for each core [`LogEntry`] field. Dotted keys
+/// match nested objects (`log.level`).
+#[derive(Debug, Clone)]
+pub struct JsonKeys {
+    pub timestamp: Vec<String>,
+    pub level: Vec<String>,
+    pub message: Vec<String>,
+    pub host: Vec<String>,
+    pub service: Vec<String>,
+}
+
+impl Default for JsonKeys {
+    fn default() -> Self {
+        let keys = |names: &[&str]| names.iter().map(|n| n.to_string()).collect();
+        Self {
+            timestamp: keys(&["@timestamp", "timestamp", "time", "ts", "@t"]),
+            level: keys(&["level", "lvl", "severity", "log.level", "levelname", "@l"]),
+            message: keys(&["message", "msg", "event", "@m", "@mt"]),
+            host: keys(&["host", "hostname", "host.name"]),
+            service: keys(&["service", "service.name", "logger", "name", "app"]),
+        }
+    }
+}
+
+impl JsonKeys {
+    /// Replace the keys for one field from a `FIELD=KEY[,KEY...]` spec,
+    /// e.g. `timestamp=created_at` or `message=text,msg`.
+    pub fn set(&mut self, spec: &str) -> Result<()> {
+        let Some((field, keys)) = spec.split_once('=') else {
+            bail!("expected FIELD=KEY[,KEY...], got '{}'", spec);
+        };
+        let slot = match field {
+            "timestamp" => &mut self.timestamp,
+            "level" => &mut self.level,
+            "message" => &mut self.message,
+            "host" => &mut self.host,
+            "service" => &mut self.service,
+            other => bail!(
+                "unknown field '{}' (expected timestamp, level, message, host or service)",
+                other
+            ),
+        };
+        *slot = keys
+            .split(',')
+            .map(str::trim)
+            .filter(|k| !k.is_empty())
+            .map(str::to_string)
+            .collect();
+        Ok(())
+    }
+}
+
+pub struct JsonParser {
+    keys: JsonKeys,
+}
+
+impl JsonParser {
+    pub fn new(keys: JsonKeys) -> Self {
+        Self { keys }
+    }
+}
+
+impl Default for JsonParser {
+    fn default() -> Self {
+        Self::new(JsonKeys::default())
+    }
+}
+
+impl LogParser for JsonParser {
+    fn name(&self) -> &str {
+        "json"
+    }
+
+    fn aliases(&self) -> &[&str] {
+        &["jsonl", "ndjson"]
+    }
+
+    fn description(&self) -> &str {
+        "JSON lines (bunyan, pino, zap, logrus, structlog, serilog)"
+    }
+
+    fn parse_line(&self, line: &[u8]) -> Option<LogEntry> {
+        let line = std::str::from_utf8(line).ok()?.trim();
+        if !line.starts_with('{') {
+            return None;
+        }
+        let Value::Object(object) = parse(line)? else {
+            return None;
+        };
+
+        let mut fields = Fields::with_capacity(object.len());
+        flatten("", object, &mut fields);
+        Some(self.build_entry(fields))
+    }
+
+    /// An object with at least one of the configured core keys.
+    fn sniff(&self, line: &[u8]) -> bool {
+        self.parse_line(line).is_some_and(|entry| {
+            entry.timestamp.is_some() || entry.level.is_some() || entry.message.is_some()
+        })
+    }
+}
+
+impl JsonParser {
+    fn build_entry(&self, mut fields: Fields) -> LogEntry {
+        let mut take = |keys: &[String]| keys.iter().find_map(|k| fields.shift_remove(k));
+
+        let timestamp = take(&self.keys.timestamp).and_then(into_string);
+        let level = take(&self.keys.level).and_then(|v| level(&v));
+        let message = take(&self.keys.message).and_then(into_string);
+        let host = take(&self.keys.host).and_then(into_string);
+        let service = take(&self.keys.service).and_then(into_string);
+
+        LogEntry {
+            timestamp,
+            host,
+            service,
+            level,
+            message,
+            fields,
+            ..Default::default()
+        }
+    }
+}
+
+/// Copy `object` into `out`, naming nested object members `parent.child`.
+fn flatten(prefix: &str, object: serde_json::Map<String, Value>, out: &mut Fields) {
+    for (key, value) in object {
+        let key = if prefix.is_empty() {
+            key
+        } else {
+            format!("{}.{}", prefix, key)
+        };
+        match value {
+            Value::Object(nested) => flatten(&key, nested, out),
+            value => {
+                if let Some(value) = field_value(value) {
+                    out.insert(key, value);
+                }
+            }
+        }
+    }
+}
+
+/// JSON to [`FieldValue`], keeping objects inside arrays as maps; `null`
+/// is dropped.
+fn field_value(value: Value) -> Option<FieldValue> {
+    Some(match value {
+        Value::Null => return None,
+        Value::Bool(b) => FieldValue::Bool(b),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => FieldValue::Int(i),
+            None => FieldValue::Float(n.as_f64()?),
+        },
+        Value::String(s) => FieldValue::String(s),
+        Value::Array(items) => {
+            FieldValue::List(items.into_iter().filter_map(field_value).collect())
+        }
+        Value::Object(object) => FieldValue::Map(
+            object
+                .into_iter()
+                .filter_map(|(k, v)| Some((k, field_value(v)?)))
+                .collect(),
+        ),
+    })
+}
+
+/// Strings as-is and numbers in decimal (epoch timestamps are resolved by
+/// the normalizer).
+fn into_string(value: FieldValue) -> Option<String> {
+    match value {
+        FieldValue::String(s) => Some(s),
+        FieldValue::Int(i) => Some(i.to_string()),
+        FieldValue::Float(f) => Some(f.to_string()),
+        FieldValue::Bool(b) => Some(b.to_string()),
+        FieldValue::List(_) | FieldValue::Map(_) => None,
+    }
+}
+
+/// Level names, syslog numbers (0-7) or bunyan/pino numbers (10 trace ..
+/// 60 fatal).
+fn level(value: &FieldValue) -> Option<Severity> {
+    match value {
+        FieldValue::String(s) => Severity::from_python(s).or_else(|| s.parse().ok()),
+        FieldValue::Int(n @ 0..=7) => Severity::from_syslog(*n as u8),
+        FieldValue::Int(n) => Some(match *n {
+            ..=10 => Severity::Trace,
+            11..=20 => Severity::Debug,
+            21..=30 => Severity::Info,
+            31..=40 => Severity::Warn,
+            41..=50 => Severity::Error,
+            _ => Severity::Critical,
+        }),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_bunyan_fields() {
+        let entry = JsonParser::default()
+            .parse_line(
+                br#"{"name":"api","hostname":"web01","pid":812,"level":30,"msg":"listening","time":"2025-08-31T22:50:01.234Z","v":0}"#,
+            )
+            .unwrap();
+        assert_eq!(entry.timestamp.as_deref(), Some("2025-08-31T22:50:01.234Z"));
+        assert_eq!(entry.service.as_deref(), Some("api"));
+        assert_eq!(entry.host.as_deref(), Some("web01"));
+        assert_eq!(entry.level, Some(Severity::Info));
+        assert_eq!(entry.message.as_deref(), Some("listening"));
+        assert_eq!(entry.fields.get("pid"), Some(&FieldValue::Int(812)));
+    }
+
+    #[test]
+    fn flattens_objects_and_drops_nulls() {
+        let entry = JsonParser::default()
+            .parse_line(
+                br#"{"level":"error","ts":1756680601.5,"logger":"db","msg":"query failed","error":{"code":"57014","detail":null}}"#,
+            )
+            .unwrap();
+        assert_eq!(entry.timestamp.as_deref(), Some("1756680601.5"));
+        assert_eq!(entry.level, Some(Severity::Error));
+        assert_eq!(entry.service.as_deref(), Some("db"));
+        assert_eq!(
+            entry.fields.get("error.code"),
+            Some(&FieldValue::from("57014"))
+        );
+        assert!(!entry.fields.contains_key("error.detail"));
+    }
+
+    #[test]
+    fn keeps_arrays_as_lists() {
+        let entry = JsonParser::default()
+            .parse_line(br#"{"msg":"tagged","tags":["a",{"k":1}]}"#)
+            .unwrap();
+        let mut nested = Fields::new();
+        nested.insert("k".into(), FieldValue::Int(1));
+        assert_eq!(
+            entry.fields.get("tags"),
+            Some(&FieldValue::List(vec!["a".into(), nested.into()]))
+        );
+    }
+
+    #[test]
+    fn reads_serilog_compact_json() {
+        let entry = JsonParser::default()
+            .parse_line(
+                br#"{"@t":"2025-08-31T22:50:02.0000000Z","@mt":"User {UserId} logged in","@l":"Warning","UserId":7}"#,
+            )
+            .unwrap();
+        assert_eq!(entry.level, Some(Severity::Warn));
+        assert_eq!(entry.message.as_deref(), Some("User {UserId} logged in"));
+    }
+
+    #[test]
+    fn reads_nested_level_keys() {
+        let entry = JsonParser::default()
+            .parse_line(
+                br#"{"timestamp":"2025-08-31T22:50:03Z","event":"cache miss","log":{"level":"debug"}}"#,
+            )
+            .unwrap();
+        assert_eq!(entry.level, Some(Severity::Debug));
+        assert_eq!(entry.message.as_deref(), Some("cache miss"));
+    }
+
+    #[test]
+    fn custom_keys_take_precedence() {
+        let mut keys = JsonKeys::default();
+        keys.set("message=text").unwrap();
+        let entry = JsonParser::new(keys)
+            .parse_line(br#"{"text":"hi","msg":"kept"}"#)
+            .unwrap();
+        assert_eq!(entry.message.as_deref(), Some("hi"));
+        assert_eq!(entry.fields.get("msg"), Some(&FieldValue::from("kept")));
+    }
+
+    #[test]
+    fn rejects_unknown_key_targets() {
+        let mut keys = JsonKeys::default();
+        assert!(keys.set("colour=red").is_err());
+    }
+
+    #[test]
+    fn rejects_non_objects() {
+        assert!(JsonParser::default().parse_line(b"not json").is_none());
+        assert!(JsonParser::default().parse_line(b"[1,2,3]").is_none());
+    }
+
+    #[test]
+    fn rejects_cut_off_objects() {
+        assert!(
+            JsonParser::default()
+                .parse_line(br#"{"msg":"cut"#)
+                .is_none()
+        );
+    }
+}
@@ -0,0 +1,598 @@
+//! Access logs in a custom layout, compiled from the server's own format
+//! string: an nginx `log_format` (`$remote_addr - $remote_user [$time_local]
+//! "$request" $status ...`) or an Apache `LogFormat` (`%h %l %u %t \"%r\"
+//! %>s %b %D ...`).
+//!
+//! The format is compiled once into literals and fields. A field ends at
+//! the next literal; inside `"..."` it ends at the next unescaped quote, so
+//! quoted values may contain the separator. Well-known variables fill the
+//! core fields and `http`; any other variable is kept in `fields` under its
+//! name (nginx) or a lowercase header/directive name (Apache).
+
+use crate::config::{FieldValue, HttpRequest, LogEntry};
+use crate::parsers::LogParser;
+use crate::severity::Severity;
+use anyhow::{Result, anyhow, bail};
+use chrono::{DateTime, NaiveDateTime};
+use memchr::{memchr, memmem};
+
+/// A compiled nginx or Apache access log format.
+#[derive(Debug)]
+pub struct LogFormat {
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug)]
+enum Segment {
+    Literal(String),
+    Field { target: Target, quoted: bool },
+}
+
+/// Where a variable's value goes.
+#[derive(Debug, Clone, PartialEq)]
+enum Target {
+    Host,
+    RemoteUser,
+    Timestamp,
+    /// Apache `%t`, written with its brackets.
+    BracketedTimestamp,
+    /// Apache `%{format}t` with a strftime format.
+    Strftime(String),
+    Request,
+    Method,
+    Path,
+    Query,
+    Protocol,
+    Status,
+    Bytes,
+    Referrer,
+    UserAgent,
+    DurationSecs,
+    DurationMillis,
+    DurationMicros,
+    Field(String),
+}
+
+impl LogFormat {
+    /// Compile an nginx `log_format` string. A whole directive
+    /// (`log_format main '...' '...';`) is accepted too.
+    pub fn nginx(format: &str) -> Result<Self> {
+        let format = nginx_directive(format).unwrap_or_else(|| format.to_string());
+        let mut builder = Builder::default();
+        let mut rest = format.as_str();
+
+        while let Some(dollar) = rest.find('$') {
+            builder.literal.push_str(&rest[..dollar]);
+            rest = &rest[dollar + 1..];
+            let (name, after) = match rest.strip_prefix('{') {
+                Some(braced) => braced
+                    .split_once('}')
+                    .ok_or_else(|| anyhow!("unterminated ${{ in log_format"))?,
+                None => {
+                    let len = rest
+                        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                        .unwrap_or(rest.len());
+                    rest.split_at(len)
+                }
+            };
+            if name.is_empty() {
+                builder.literal.push('$');
+                continue;
+            }
+            builder.field(nginx_target(name), name)?;
+            rest = after;
+        }
+        builder.literal.push_str(rest);
+        builder.finish()
+    }
+
+    /// Compile an Apache `LogFormat` string. A whole directive
+    /// (`LogFormat "..." combined`) is accepted too, and `\"` is read as a
+    /// quote either way.
+    pub fn apache(format: &str) -> Result<Self> {
+        let format = apache_directive(format).unwrap_or(format);
+        let format = format
+            .replace("\\\"", "\"")
+            .replace("\\t", "\t")
+            .replace("\\n", "\n");
+        let mut builder = Builder::default();
+        let mut rest = format.as_str();
+
+        while let Some(pct) = rest.find('%') {
+            builder.literal.push_str(&rest[..pct]);
+            rest = &rest[pct + 1..];
+            if let Some(after) = rest.strip_prefix('%') {
+                builder.literal.push('%');
+                rest = after;
+                continue;
+            }
+            // `<`/`>` (original/final request) and status conditions (`!200,304`)
+            rest = rest.trim_start_matches(|c: char| "<>!,".contains(c) || c.is_ascii_digit());
+            let arg = match rest.strip_prefix('{') {
+                Some(braced) => {
+                    let (arg, after) = braced
+                        .split_once('}')
+                        .ok_or_else(|| anyhow!("unterminated %{{ in LogFormat"))?;
+                    rest = after;
+                    Some(arg)
+                }
+                None => None,
+            };
+            let directive = rest
+                .chars()
+                .next()
+                .ok_or_else(|| anyhow!("LogFormat ends with '%'"))?;
+            rest = &rest[directive.len_utf8()..];
+            builder.field(apache_target(directive, arg)?, &format!("%{}", directive))?;
+        }
+        builder.literal.push_str(rest);
+        builder.finish()
+    }
+
+    /// Match a line against the format.
+    pub fn parse_line(&self, line: &str) -> Option<LogEntry> {
+        let mut entry = LogEntry::default();
+        let mut http = HttpRequest::default();
+        let mut pos = 0;
+
+        for (i, segment) in self.segments.iter().enumerate() {
+            let rest = &line[pos..];
+            match segment {
+                Segment::Literal(lit) => {
+                    if !rest.starts_with(lit.as_str()) {
+                        return None;
+                    }
+                    pos += lit.len();
+                }
+                Segment::Field { target, quoted } => {
+                    let len = match self.segments.get(i + 1) {
+                        Some(Segment::Literal(lit)) => {
+                            value_len(rest, lit, *quoted, target == &Target::BracketedTimestamp)?
+                        }
+                        _ => rest.len(),
+                    };
+                    apply(&mut entry, &mut http, target, &rest[..len])?;
+                    pos += len;
+                }
+            }
+        }
+        if !line[pos..].trim_end().is_empty() {
+            return None;
+        }
+
+        let status = http.status;
+        entry.level = Some(status.map_or(Severity::Info, Severity::from_http_status));
+        entry.message = Some(match (&http.method, &http.path, status) {
+            (Some(method), Some(path), Some(status)) => {
+                format!("{} {} -> {}", method, path, status)
+            }
+            _ => line.trim_end().to_string(),
+        });
+        if http != HttpRequest::default() {
+            entry.http = Some(http);
+        }
+        Some(entry)
+    }
+}
+
+#[derive(Default)]
+struct Builder {
+    segments: Vec<Segment>,
+    literal: String,
+}
+
+impl Builder {
+    fn field(&mut self, target: Target, name: &str) -> Result<()> {
+        if !self.literal.is_empty() {
+            self.segments
+                .push(Segment::Literal(std::mem::take(&mut self.literal)));
+        } else if matches!(self.segments.last(), Some(Segment::Field { .. })) {
+            bail!("{} must be separated from the variable before it", name);
+        }
+        self.segments.push(Segment::Field {
+            target,
+            quoted: false,
+        });
+        Ok(())
+    }
+
+    fn finish(mut self) -> Result<LogFormat> {
+        if !self.literal.is_empty() {
+            self.segments.push(Segment::Literal(self.literal));
+        }
+        if !self
+            .segments
+            .iter()
+            .any(|s| matches!(s, Segment::Field { .. }))
+        {
+            bail!("format has no variables");
+        }
+
+        // a field between `"` literals is a quoted value
+        for i in 0..self.segments.len() {
+            let opens =
+                i > 0 && matches!(&self.segments[i - 1], Segment::Literal(l) if l.ends_with('"'));
+            let closes =
+                matches!(self.segments.get(i + 1), Some(Segment::Literal(l)) if l.starts_with('"'));
+            if let Segment::Field { quoted, .. } = &mut self.segments[i] {
+                *quoted = opens && closes;
+            }
+        }
+        Ok(LogFormat {
+            segments: self.segments,
+        })
+    }
+}
+
+/// Length of the value at the start of `rest`, which is followed by `lit`.
+fn value_len(rest: &str, lit: &str, quoted: bool, bracketed: bool) -> Option<usize> {
+    let b = rest.as_bytes();
+    if bracketed {
+        // [10/Oct/2000:13:55:36 -0700]
+        let end = memchr(b']', b)? + 1;
+        return rest[end..].starts_with(lit).then_some(end);
+    }
+    if quoted {
+        // nginx writes `"` as `\x22`, Apache as `\"`
+        let mut i = 0;
+        while let Some(q) = memchr(b'"', &b[i..]) {
+            let at = i + q;
+            if at > 0 && b[at - 1] == b'\\' {
+                i = at + 1;
+                continue;
+            }
+            return rest[at..].starts_with(lit).then_some(at);
+        }
+        return None;
+    }
+    memmem::find(b, lit.as_bytes())
+}
+
+/// Store a matched value. `-` (not available) is skipped.
+fn apply(entry: &mut LogEntry, http: &mut HttpRequest, target: &Target, value: &str) -> Option<()> {
+    if value == "-" || value.is_empty() {
+        // `%b` / `$bytes_sent`-style fields log an empty body as `-`
+        if matches!(target, Target::Bytes) {
+            http.bytes = Some(0);
+        }
+        return Some(());
+    }
+    let text = || Some(value.to_string());
+    match target {
+        Target::Host => entry.host = text(),
+        Target::RemoteUser => http.remote_user = text(),
+        Target::Timestamp => entry.timestamp = text(),
+        Target::BracketedTimestamp => {
+            entry.timestamp = Some(value.strip_prefix('[')?.strip_suffix(']')?.to_string())
+        }
+        // a format without a full date and time (`%{%z}t`, `%{%H:%M}t`) can't
+        // be a timestamp, so its text is kept as a field instead
+        Target::Strftime(format) => {
+            let parsed = DateTime::parse_from_str(value, format)
+                .map(|ts| ts.to_rfc3339())
+                .or_else(|_| {
+                    NaiveDateTime::parse_from_str(value, format)
+                        .map(|ts| ts.format("%Y-%m-%dT%H:%M:%S%.f").to_string())
+                });
+            match parsed {
+                Ok(ts) => entry.timestamp = Some(ts),
+                Err(_) => entry.set_field("time", value),
+            }
+        }
+        Target::Request => {
+            let mut parts = value.splitn(3, ' ');
+            http.method = parts.next().map(str::to_string);
+            http.path = parts.next().map(str::to_string);
+            http.protocol = parts.next().map(str::to_string);
+        }
+        Target::Method => http.method = text(),
+        Target::Path => {
+            http.path.get_or_insert_with(|| value.to_string());
+        }
+        Target::Query => {
+            if let Some(path) = &mut http.path
+                && !path.contains('?')
+            {
+                if !value.starts_with('?') {
+                    path.push('?');
+                }
+                path.push_str(value);
+            }
+        }
+        Target::Protocol => http.protocol = text(),
+        Target::Status => http.status = Some(value.parse().ok()?),
+        Target::Bytes => http.bytes = Some(value.parse().ok()?),
+        Target::Referrer => http.referrer = text(),
+        Target::UserAgent => http.user_agent = text(),
+        Target::DurationSecs => http.duration_ms = Some(value.parse::<f64>().ok()? * 1000.0),
+        Target::DurationMillis => http.duration_ms = Some(value.parse().ok()?),
+        Target::DurationMicros => http.duration_ms = Some(value.parse::<f64>().ok()? / 1000.0),
+        Target::Field(name) => entry.set_field(name.as_str(), typed(value)),
+    }
+    Some(())
+}
+
+/// Integers and decimals as numbers, anything else as text.
+fn typed(value: &str) -> FieldValue {
+    if let Ok(i) = value.parse::<i64>() {
+        return FieldValue::Int(i);
+    }
+    if value.contains('.')
+        && value.bytes().all(|b| b.is_ascii_digit() || b == b'.')
+        && let Ok(f) = value.parse::<f64>()
+    {
+        return FieldValue::Float(f);
+    }
+    value.into()
+}
+
+fn nginx_target(name: &str) -> Target {
+    match name {
+        "remote_addr" | "realip_remote_addr" => Target::Host,
+        "remote_user" => Target::RemoteUser,
+        "time_local" | "time_iso8601" | "msec" => Target::Timestamp,
+        "request" => Target::Request,
+        "request_method" => Target::Method,
+        "request_uri" | "uri" | "document_uri" => Target::Path,
+        "args" | "query_string" => Target::Query,
+        "server_protocol" => Target::Protocol,
+        "status" => Target::Status,
+        "body_bytes_sent" => Target::Bytes,
+        "http_referer" => Target::Referrer,
+        "http_user_agent" => Target::UserAgent,
+        "request_time" => Target::DurationSecs,
+        "host" => Target::Field("http_host".to_string()),
+        other => Target::Field(other.to_string()),
+    }
+}
+
+fn apache_target(directive: char, arg: Option<&str>) -> Result<Target> {
+    let header = |arg: Option<&str>| {
+        arg.unwrap_or_default()
+            .to_ascii_lowercase()
+            .replace('-', "_")
+    };
+    Ok(match directive {
+        'h' | 'a' => Target::Host,
+        'u' => Target::RemoteUser,
+        't' => match arg.map(|arg| arg.trim_start_matches("begin:").trim_start_matches("end:")) {
+            None => Target::BracketedTimestamp,
+            // epoch time, which the normalizer reads as is
+            Some("sec" | "msec" | "usec") => Target::Timestamp,
+            Some(frac @ ("msec_frac" | "usec_frac")) => Target::Field(frac.to_string()),
+            Some(format) => Target::Strftime(format.to_string()),
+        },
+        'r' => Target::Request,
+        'm' => Target::Method,
+        'U' => Target::Path,
+        'q' => Target::Query,
+        'H' => Target::Protocol,
+        's' => Target::Status,
+        'b' | 'B' => Target::Bytes,
+        'D' => Target::DurationMicros,
+        'T' => match arg {
+            Some("ms") => Target::DurationMillis,
+            Some("us") => Target::DurationMicros,
+            _ => Target::DurationSecs,
+        },
+        'i' => match header(arg).as_str() {
+            "referer" => Target::Referrer,
+            "user_agent" => Target::UserAgent,
+            "host" => Target::Field("http_host".to_string()),
+            name => Target::Field(name.to_string()),
+        },
+        'o' | 'e' | 'n' | 'C' | 'x' | '^' => Target::Field(header(arg)),
+        'l' => Target::Field("ident".to_string()),
+        'v' | 'V' => Target::Field("server_name".to_string()),
+        'p' => Target::Field("port".to_string()),
+        'P' => Target::Field("pid".to_string()),
+        'O' => Target::Field("bytes_sent".to_string()),
+        'I' => Target::Field("bytes_received".to_string()),
+        'S' => Target::Field("bytes_transferred".to_string()),
+        'X' => Target::Field("connection_status".to_string()),
+        'k' => Target::Field("keepalive_requests".to_string()),
+        'L' => Target::Field("log_id".to_string()),
+        'R' => Target::Field("handler".to_string()),
+        'f' => Target::Field("filename".to_string()),
+        'A' => Target::Field("local_addr".to_string()),
+        other => bail!("unsupported LogFormat directive '%{}'", other),
+    })
+}
+
+/// The format of `log_format name 'part' 'part' ...;`: its quoted parts,
+/// concatenated.
+fn nginx_directive(s: &str) -> Option<String> {
+    let rest = s.trim().strip_prefix("log_format")?;
+    let mut format = String::new();
+    let mut rest = rest.trim_start();
+    // skip the format name (and an optional `escape=...`)
+    while !rest.starts_with(['\'', '"']) {
+        rest = rest.split_once(char::is_whitespace)?.1.trim_start();
+    }
+    while let Some(quote) = rest.chars().next().filter(|c| matches!(c, '\'' | '"')) {
+        let (part, after) = rest[1..].split_once(quote)?;
+        format.push_str(part);
+        rest = after.trim_start();
+    }
+    Some(format)
+}
+
+/// The format of `LogFormat "..." [nickname]`.
+fn apache_directive(s: &str) -> Option<&str> {
+    let rest = s.trim().strip_prefix("LogFormat")?.trim_start();
+    let body = rest.strip_prefix('"')?;
+    let bytes = body.as_bytes();
+    let mut i = 0;
+    while let Some(q) = memchr(b'"', &bytes[i..]) {
+        let at = i + q;
+        if at > 0 && bytes[at - 1] == b'\\' {
+            i = at + 1;
+            continue;
+        }
+        return Some(&body[..at]);
+    }
+    None
+}
+
+/// A [`LogFormat`] as a registrable parser, named `nginx_format` or
+/// `apache_format`.
+pub struct LogFormatParser {
+    name: &'static str,
+    format: LogFormat,
+}
+
+impl LogFormatParser {
+    /// Parser for an nginx `log_format` (see [`LogFormat::nginx`]).
+    pub fn nginx(format: &str) -> Result<Self> {
+        Ok(Self {
+            name: "nginx_format",
+            format: LogFormat::nginx(format)?,
+        })
+    }
+
+    /// Parser for an Apache `LogFormat` (see [`LogFormat::apache`]).
+    pub fn apache(format: &str) -> Result<Self> {
+        Ok(Self {
+            name: "apache_format",
+            format: LogFormat::apache(format)?,
+        })
+    }
+}
+
+impl LogParser for LogFormatParser {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn description(&self) -> &str {
+        match self.name {
+            "nginx_format" => "Nginx access logs in a custom --nginx-format log_format",
+            _ => "Apache access logs in a custom --apache-format LogFormat",
+        }
+    }
+
+    fn parse_line(&self, line: &[u8]) -> Option<LogEntry> {
+        self.format.parse_line(std::str::from_utf8(line).ok()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsers::apache::ApacheParser;
+
+    #[test]
+    fn compiles_nginx_and_apache_formats() {
+        let nginx = LogFormatParser::nginx(
+            r#"log_format timed '$remote_addr - $remote_user [$time_local] "$request" '
+                '$status $body_bytes_sent "$http_referer" "$http_user_agent" '
+                'rt=$request_time urt="$upstream_response_time" xff="$http_x_forwarded_for" $host';"#,
+        )
+        .unwrap();
+        let input = concat!(
+            r#"10.0.0.1 - bob [12/May/2025:06:25:24 +0000] "GET /a?b=1 HTTP/1.1" 502 157 "-" "curl/8 \x22q\x22" "#,
+            r#"rt=0.250 urt="0.249, 0.001" xff="203.0.113.7, 10.0.0.2" example.com"#,
+            "\nnot an access line\n"
+        );
+        let v = nginx.parse(input).unwrap();
+        assert_eq!(v.len(), 1);
+        assert_eq!(v[0].host.as_deref(), Some("10.0.0.1"));
+        assert_eq!(
+            v[0].timestamp.as_deref(),
+            Some("12/May/2025:06:25:24 +0000")
+        );
+        assert_eq!(v[0].level, Some(Severity::Error));
+        assert_eq!(v[0].message.as_deref(), Some("GET /a?b=1 -> 502"));
+        let http = v[0].http.as_ref().unwrap();
+        assert_eq!(http.remote_user.as_deref(), Some("bob"));
+        assert_eq!((http.status, http.bytes), (Some(502), Some(157)));
+        assert_eq!(http.referrer, None);
+        assert_eq!(http.user_agent.as_deref(), Some(r"curl/8 \x22q\x22"));
+        assert_eq!(http.duration_ms, Some(250.0));
+        assert_eq!(
+            v[0].fields.get("upstream_response_time"),
+            Some(&FieldValue::from("0.249, 0.001"))
+        );
+        assert_eq!(
+            v[0].fields.get("http_x_forwarded_for"),
+            Some(&FieldValue::from("203.0.113.7, 10.0.0.2"))
+        );
+        assert_eq!(
+            v[0].fields.get("http_host"),
+            Some(&FieldValue::from("example.com"))
+        );
+
+        let apache = LogFormat::apache(
+            r#"%h %l %u %t \"%r\" %>s %b \"%{Referer}i\" \"%{User-Agent}i\" %D %{X-Request-Id}i"#,
+        )
+        .unwrap();
+        let line = r#"::1 - - [10/Oct/2000:13:55:36 -0700] "POST /login HTTP/2.0" 200 - "https://r/" "Mozilla \"x\"" 3885 abc-123"#;
+        let entry = apache.parse_line(line).unwrap();
+        assert_eq!(
+            entry.timestamp.as_deref(),
+            Some("10/Oct/2000:13:55:36 -0700")
+        );
+        let http = entry.http.as_ref().unwrap();
+        assert_eq!(http.method.as_deref(), Some("POST"));
+        // `-` bytes is an empty body, as in the `apache` preset
+        assert_eq!(http.bytes, Some(0));
+        let preset = ApacheParser.parse_line(line.as_bytes()).unwrap();
+        assert_eq!(preset.http.unwrap().bytes, http.bytes);
+        assert_eq!(http.referrer.as_deref(), Some("https://r/"));
+        assert_eq!(http.user_agent.as_deref(), Some(r#"Mozilla \"x\""#));
+        assert_eq!(http.duration_ms, Some(3.885));
+        assert_eq!(
+            entry.fields.get("x_request_id"),
+            Some(&FieldValue::from("abc-123"))
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_formats() {
+        assert!(LogFormat::apache("%h %Z").is_err());
+        assert!(LogFormat::apache("%h %{Referer").is_err());
+        assert!(LogFormat::nginx("$remote_addr$status").is_err());
+        assert!(LogFormat::nginx("${remote_addr").is_err());
+        assert!(LogFormat::nginx("no variables").is_err());
+    }
+
+    #[test]
+    fn apache_strftime_time_is_parsed_with_its_format() {
+        let zoned = LogFormat::apache(r#"%h %{%Y-%m-%d %H:%M:%S %z}t \"%r\" %>s"#).unwrap();
+        let entry = zoned
+            .parse_line(r#"10.0.0.1 2025-05-12 06:25:24 +0200 "GET / HTTP/1.1" 200"#)
+            .unwrap();
+        assert_eq!(
+            entry.timestamp.as_deref(),
+            Some("2025-05-12T06:25:24+02:00")
+        );
+
+        let local = LogFormat::apache(r#"%h [%{begin:%d/%b/%Y %T}t] \"%r\" %>s"#).unwrap();
+        let entry = local
+            .parse_line(r#"10.0.0.1 [12/May/2025 06:25:24] "GET / HTTP/1.1" 200"#)
+            .unwrap();
+        assert_eq!(entry.timestamp.as_deref(), Some("2025-05-12T06:25:24"));
+    }
+
+    #[test]
+    fn apache_strftime_time_without_a_date_is_a_field() {
+        let format = LogFormat::apache(r#"%h [%{%H:%M:%S}t] \"%r\" %>s"#).unwrap();
+        let entry = format
+            .parse_line(r#"10.0.0.1 [06:25:24] "GET / HTTP/1.1" 200"#)
+            .unwrap();
+        assert_eq!(entry.timestamp, None);
+        assert_eq!(
+            entry.fields.get("time"),
+            Some(&FieldValue::from("06:25:24"))
+        );
+    }
+
+    #[test]
+    fn apache_epoch_time_is_kept_for_the_normalizer() {
+        let format = LogFormat::apache(r#"%h %{msec}t \"%r\" %>s"#).unwrap();
+        let entry = format
+            .parse_line(r#"10.0.0.1 1747031124123 "GET / HTTP/1.1" 200"#)
+            .unwrap();
+        assert_eq!(entry.timestamp.as_deref(), Some("1747031124123"));
+    }
+}
@@ -0,0 +1,304 @@
+//! logfmt (`time=2025-08-31T22:50:01Z level=info msg="started" dur=12ms`),
+//! as written by Go's logfmt/slog/logrus text handlers and Heroku routers.
+//!
+//! Values are bare up to the next space, or double-quoted with backslash
+//! escapes (`\"`, `\\`, `\n`, `\t`, `\r`, `\uXXXX`). A key without `=` is a
+//! `true` flag. Standard keys fill the core fields; the rest are kept in
+//! `fields`, with bare numbers typed as integers or floats.
+
+use crate::config::{FieldValue, LogEntry};
+use crate::parsers::LogParser;
+use memchr::{memchr, memchr2, memchr3};
+
+const TIMESTAMP_KEYS: [&str; 4] = ["time", "ts", "timestamp", "t"];
+/// `at` is the level in Heroku router and platform logs.
+const LEVEL_KEYS: [&str; 4] = ["level", "lvl", "severity", "at"];
+const MESSAGE_KEYS: [&str; 3] = ["msg", "message", "event"];
+const HOST_KEYS: [&str; 2] = ["hostname", "host"];
+const SERVICE_KEYS: [&str; 4] = ["service", "app", "logger", "component"];
+
+pub struct LogfmtParser;
+
+impl LogParser for LogfmtParser {
+    fn name(&self) -> &str {
+        "logfmt"
+    }
+
+    fn description(&self) -> &str {
+        "logfmt key=value lines (Go slog/logrus, Heroku)"
+    }
+
+    fn parse_line(&self, line: &[u8]) -> Option<LogEntry> {
+        parse_line(line)
+    }
+
+    /// At least two pairs, one of them a standard key. journald export
+    /// fields (`MESSAGE=...`) are uppercase and never match.
+    fn sniff(&self, line: &[u8]) -> bool {
+        let mut pairs = Pairs::new(line);
+        let mut count = 0;
+        let mut standard = false;
+        for (key, value) in pairs.by_ref() {
+            count += 1;
+            standard |= value.is_some() && is_standard_key(key);
+        }
+        pairs.valid && count >= 2 && standard
+    }
+}
+
+fn parse_line(line: &[u8]) -> Option<LogEntry> {
+    // a line that isn't UTF-8 is rejected rather than decoded lossily
+    std::str::from_utf8(line).ok()?;
+    let mut entry = LogEntry::default();
+    let mut pairs = Pairs::new(line);
+    let mut has_value = false;
+
+    for (key, value) in pairs.by_ref() {
+        let key = std::str::from_utf8(key).ok()?;
+        let Some(value) = value else {
+            entry.set_field(key, true);
+            continue;
+        };
+        has_value = true;
+
+        if LEVEL_KEYS.contains(&key)
+            && entry.level.is_none()
+            && let Ok(level) = value.as_str().parse()
+        {
+            entry.level = Some(level);
+            continue;
+        }
+        let slot = if TIMESTAMP_KEYS.contains(&key) {
+            Some(&mut entry.timestamp)
+        } else if MESSAGE_KEYS.contains(&key) {
+            Some(&mut entry.message)
+        } else if HOST_KEYS.contains(&key) {
+            Some(&mut entry.host)
+        } else if SERVICE_KEYS.contains(&key) {
+            Some(&mut entry.service)
+        } else {
+            None
+        };
+        // repeated standard keys are kept as attributes
+        match slot {
+            Some(slot) if slot.is_none() => *slot = Some(value.into_string()),
+            _ => entry.set_field(key, value.into_field()),
+        }
+    }
+
+    (pairs.valid && has_value).then_some(entry)
+}
+
+fn is_standard_key(key: &[u8]) -> bool {
+    let Ok(key) = std::str::from_utf8(key) else {
+        return false;
+    };
+    [
+        &TIMESTAMP_KEYS[..],
+        &LEVEL_KEYS,
+        &MESSAGE_KEYS,
+        &HOST_KEYS,
+        &SERVICE_KEYS,
+    ]
+    .iter()
+    .any(|keys| keys.contains(&key))
+}
+
+/// A value as written: bare, or quoted with its escapes resolved.
+enum Value<'a> {
+    Bare(&'a [u8]),
+    Quoted(String),
+}
+
+impl Value<'_> {
+    fn as_str(&self) -> &str {
+        match self {
+            Value::Bare(b) => std::str::from_utf8(b).unwrap_or_default(),
+            Value::Quoted(s) => s,
+        }
+    }
+
+    fn into_string(self) -> String {
+        match self {
+            Value::Bare(b) => String::from_utf8_lossy(b).into_owned(),
+            Value::Quoted(s) => s,
+        }
+    }
+
+    /// Bare integers and decimals become numbers; everything else a string.
+    fn into_field(self) -> FieldValue {
+        if let Value::Bare(b) = &self
+            && b.first().is_some_and(|c| c.is_ascii_digit() || *c == b'-')
+        {
+            let s = self.as_str();
+            if let Ok(i) = s.parse::<i64>() {
+                return FieldValue::Int(i);
+            }
+            if s.contains('.')
+                && let Ok(f) = s.parse::<f64>()
+            {
+                return FieldValue::Float(f);
+            }
+        }
+        FieldValue::String(self.into_string())
+    }
+}
+
+/// Iterator over `key[=value]` pairs. Stops at the first malformed pair
+/// (empty key, unterminated quote) and clears `valid`.
+struct Pairs<'a> {
+    line: &'a [u8],
+    pos: usize,
+    valid: bool,
+}
+
+impl<'a> Pairs<'a> {
+    fn new(line: &'a [u8]) -> Self {
+        Self {
+            line,
+            pos: 0,
+            valid: true,
+        }
+    }
+}
+
+impl<'a> Iterator for Pairs<'a> {
+    type Item = (&'a [u8], Option<Value<'a>>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let line = self.line;
+        while self.pos < line.len() && matches!(line[self.pos], b' ' | b'\t' | b'\r') {
+            self.pos += 1;
+        }
+        if self.pos >= line.len() || !self.valid {
+            return None;
+        }
+
+        let rest = &line[self.pos..];
+        let key_len = memchr3(b'=', b' ', b'"', rest).unwrap_or(rest.len());
+        let key = &rest[..key_len];
+        if key.is_empty() || rest.get(key_len) == Some(&b'"') {
+            self.valid = false;
+            return None;
+        }
+        self.pos += key_len;
+        if line.get(self.pos) != Some(&b'=') {
+            return Some((key, None));
+        }
+        self.pos += 1;
+
+        if line.get(self.pos) == Some(&b'"') {
+            let Some((value, used)) = unquote(&line[self.pos + 1..]) else {
+                self.valid = false;
+                return None;
+            };
+            self.pos += 1 + used;
+            return Some((key, Some(Value::Quoted(value))));
+        }
+        let rest = &line[self.pos..];
+        let end = memchr(b' ', rest).unwrap_or(rest.len());
+        self.pos += end;
+        Some((key, Some(Value::Bare(rest[..end].trim_ascii_end()))))
+    }
+}
+
+/// Decode a quoted value, starting after the opening quote. Returns the
+/// value and the bytes consumed, including the closing quote.
+fn unquote(bytes: &[u8]) -> Option<(String, usize)> {
+    let mut out = Vec::new();
+    let mut pos = 0;
+    loop {
+        let at = pos + memchr2(b'"', b'\\', &bytes[pos..])?;
+        out.extend_from_slice(&bytes[pos..at]);
+        if bytes[at] == b'"' {
+            return Some((String::from_utf8_lossy(&out).into_owned(), at + 1));
+        }
+        let escaped = *bytes.get(at + 1)?;
+        pos = at + 2;
+        match escaped {
+            b'n' => out.push(b'\n'),
+            b't' => out.push(b'\t'),
+            b'r' => out.push(b'\r'),
+            b'u' => {
+                let hex = std::str::from_utf8(bytes.get(pos..pos + 4)?).ok()?;
+                let c = char::from_u32(u32::from_str_radix(hex, 16).ok()?).unwrap_or('\u{fffd}');
+                out.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+                pos += 4;
+            }
+            other => out.push(other),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::severity::Severity;
+
+    #[test]
+    fn maps_core_keys_and_types_values() {
+        let entry = LogfmtParser
+            .parse_line(
+                b"time=2025-08-31T22:50:01.234Z level=info msg=\"server started\" addr=:8080 workers=8 ratio=0.75 tls",
+            )
+            .unwrap();
+        assert_eq!(entry.timestamp.as_deref(), Some("2025-08-31T22:50:01.234Z"));
+        assert_eq!(entry.level, Some(Severity::Info));
+        assert_eq!(entry.message.as_deref(), Some("server started"));
+        assert_eq!(entry.fields.get("addr"), Some(&FieldValue::from(":8080")));
+        assert_eq!(entry.fields.get("workers"), Some(&FieldValue::Int(8)));
+        assert_eq!(entry.fields.get("ratio"), Some(&FieldValue::Float(0.75)));
+        assert_eq!(entry.fields.get("tls"), Some(&FieldValue::Bool(true)));
+    }
+
+    #[test]
+    fn unescapes_quoted_values() {
+        let entry = LogfmtParser
+            .parse_line(
+                br#"ts=2025-08-31T22:50:02Z lvl=WARN logger=db msg="slow query: \"SELECT 1\"\n\ttook 2s" dur=2.1s"#,
+            )
+            .unwrap();
+        assert_eq!(entry.level, Some(Severity::Warn));
+        assert_eq!(entry.service.as_deref(), Some("db"));
+        assert_eq!(
+            entry.message.as_deref(),
+            Some("slow query: \"SELECT 1\"\n\ttook 2s")
+        );
+        assert_eq!(entry.fields.get("dur"), Some(&FieldValue::from("2.1s")));
+    }
+
+    #[test]
+    fn reads_heroku_router_lines() {
+        let entry = LogfmtParser
+            .parse_line(
+                br#"at=error code=H12 desc="Request timeout" method=GET path="/" host=example.com status=503"#,
+            )
+            .unwrap();
+        assert_eq!(entry.level, Some(Severity::Error));
+        assert_eq!(entry.host.as_deref(), Some("example.com"));
+        assert_eq!(entry.fields.get("status"), Some(&FieldValue::Int(503)));
+        assert_eq!(entry.fields.get("path"), Some(&FieldValue::from("/")));
+    }
+
+    #[test]
+    fn rejects_plain_text() {
+        assert!(LogfmtParser.parse_line(b"just some text").is_none());
+    }
+
+    #[test]
+    fn rejects_unterminated_quotes() {
+        assert!(LogfmtParser.parse_line(b"msg=\"unterminated").is_none());
+    }
+
+    #[test]
+    fn rejects_invalid_utf8() {
+        assert!(LogfmtParser.parse_line(b"level=info msg=caf\xff").is_none());
+    }
+
+    #[test]
+    fn sniff_needs_a_well_known_key() {
+        assert!(LogfmtParser.sniff(b"level=info msg=hi"));
+        assert!(!LogfmtParser.sniff(b"MESSAGE=hi"));
+        assert!(!LogfmtParser.sniff(b"a=1 b=2"));
+    }
+}
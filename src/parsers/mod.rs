@@ -1,19 +1,208 @@
+mod access;
 pub mod apache;
+pub mod container;
+pub mod detect;
+pub mod django;
+pub mod flask;
+pub mod generic;
+pub mod grok;
 pub mod journalctl;
+pub mod journald;
+pub mod json;
+pub mod log_format;
+pub mod logfmt;
+pub mod multiline;
 pub mod nginx;
+pub mod python_format;
 pub mod python_web;
+pub mod regex_parser;
+pub mod registry;
+pub mod rfc5424;
 pub mod syslog;
 
+pub use detect::{AUTO_PRESET, Detection, detect};
+pub use multiline::{Multiline, MultilineParser};
+pub use registry::{ParserRegistry, lookup, register, registered};
+
 use crate::config::LogEntry;
-use anyhow::{anyhow, Result};
+use anyhow::{Result, anyhow};
+use memchr::memchr_iter;
 
-pub fn parse(parser: &str, input: &str) -> Result<Vec<LogEntry>> {
-    match parser {
-        "syslog" => syslog::parse_syslog(input),
-        "nginx" => nginx::parse_nginx(input),
-        "apache" => apache::parse_apache(input),
-        "journalctl" => journalctl::parse_journal(input),
-        "python_web" => python_web::parse_python_logs(input),
-        _ => Err(anyhow!("Unknown parser: {}", parser)),
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// Tunables for the generic chunked driver used by [`LogParser::parse`].
+#[cfg(feature = "parallel")]
+const CHUNK_BYTES: usize = 4 * 1024 * 1024;
+
+/// A log format that can be resolved by name through the [`ParserRegistry`].
+///
+/// Implementors only need [`name`](LogParser::name) and
+/// [`parse_line`](LogParser::parse_line); the chunk and whole-input methods
+/// default to a memchr line loop (split across rayon workers when the
+/// `parallel` feature is enabled). Formats whose records span several lines
+/// opt into line grouping with [`multiline`](LogParser::multiline), or
+/// override [`record_start`](LogParser::record_start) so that input is only
+/// split between records.
+pub trait LogParser: Send + Sync {
+    /// Canonical preset name, e.g. `"nginx"`.
+    fn name(&self) -> &str;
+
+    /// Alternative preset names that resolve to this parser.
+    fn aliases(&self) -> &[&str] {
+        &[]
+    }
+
+    /// One-line human readable description, shown by `--list-presets`.
+    fn description(&self) -> &str {
+        ""
+    }
+
+    /// Parse a single line (without its trailing newline). The line can hold
+    /// any bytes, including invalid UTF-8, and must not make this panic.
+    fn parse_line(&self, line: &[u8]) -> Option<LogEntry>;
+
+    /// Line grouping rules for formats whose records can span several lines.
+    /// When set, the default [`parse_chunk`](LogParser::parse_chunk) and
+    /// [`record_start`](LogParser::record_start) group lines with them and
+    /// parse each group with [`parse_record`](LogParser::parse_record).
+    fn multiline(&self) -> Option<&Multiline> {
+        None
+    }
+
+    /// Parse a record from its first line and continuation lines. Defaults
+    /// to [`parse_line`](LogParser::parse_line) on the first line, with the
+    /// continuation lines appended to the message.
+    fn parse_record(&self, first: &[u8], continuation: &[&[u8]]) -> Option<LogEntry> {
+        let mut entry = self.parse_line(first)?;
+        multiline::append_lines(&mut entry, continuation);
+        Some(entry)
     }
+
+    /// Parse a record-aligned chunk and push entries into `out`.
+    fn parse_chunk(&self, bytes: &[u8], out: &mut Vec<LogEntry>) {
+        if let Some(rules) = self.multiline() {
+            rules.for_each_record(bytes, |first, rest| {
+                if let Some(entry) = self.parse_record(first, rest) {
+                    out.push(entry);
+                }
+            });
+            return;
+        }
+
+        let mut start = 0;
+        for nl in memchr_iter(b'\n', bytes) {
+            if let Some(entry) = self.parse_line(&bytes[start..nl]) {
+                out.push(entry);
+            }
+            start = nl + 1;
+        }
+        if start < bytes.len()
+            && let Some(entry) = self.parse_line(&bytes[start..])
+        {
+            out.push(entry);
+        }
+    }
+
+    /// Offset of the first record that starts at or after `pos`, or
+    /// `bytes.len()` if there is none. Chunk and batch splitters only cut
+    /// input at these offsets. `bytes` itself starts at a record boundary
+    /// (the start of the input or an offset returned before), so an
+    /// implementation never has to look back past its start. Defaults to the
+    /// next line start, or the next record start under the
+    /// [`multiline`](LogParser::multiline) rules.
+    fn record_start(&self, bytes: &[u8], pos: usize) -> usize {
+        if let Some(rules) = self.multiline() {
+            return rules.record_start(bytes, pos);
+        }
+        if pos == 0 {
+            return 0;
+        }
+        match bytes
+            .get(pos - 1..)
+            .and_then(|rest| memchr::memchr(b'\n', rest))
+        {
+            Some(nl) => pos + nl,
+            None => bytes.len(),
+        }
+    }
+
+    /// Whether [`parse_chunk`](LogParser::parse_chunk) and
+    /// [`record_start`](LogParser::record_start) group lines into records by
+    /// themselves, without [`multiline`](LogParser::multiline) rules (joined
+    /// container pieces, journal export records). [`MultilineParser`] then
+    /// applies its rules to the parsed entries instead of the raw lines.
+    fn groups_lines(&self) -> bool {
+        false
+    }
+
+    /// Parse a complete input buffer.
+    fn parse(&self, input: &str) -> Result<Vec<LogEntry>> {
+        self.parse_bytes(input.as_bytes())
+    }
+
+    /// Parse a complete input buffer that may not be UTF-8, such as a
+    /// mapped file (binary `journalctl -o export` fields, stray bytes in
+    /// text logs). Lines that are not valid UTF-8 are left to
+    /// [`parse_line`](LogParser::parse_line) to reject.
+    fn parse_bytes(&self, input: &[u8]) -> Result<Vec<LogEntry>> {
+        Ok(parse_chunked(self, input))
+    }
+
+    /// Cheap format check used for sniffing. Defaults to "does it parse".
+    fn sniff(&self, line: &[u8]) -> bool {
+        self.parse_line(line).is_some()
+    }
+
+    /// Whether a sniffed line shows traits of this format that other formats
+    /// accepting the same line don't write. Breaks ties in format detection;
+    /// defaults to never.
+    fn sniff_specific(&self, _line: &[u8]) -> bool {
+        false
+    }
+}
+
+/// Run `parser` over `bytes`, splitting it at record boundaries (see
+/// [`LogParser::record_start`]) into chunks that are parsed in parallel when
+/// the `parallel` feature is enabled.
+pub fn parse_chunked<P: LogParser + ?Sized>(parser: &P, bytes: &[u8]) -> Vec<LogEntry> {
+    #[cfg(feature = "parallel")]
+    {
+        if bytes.len() > CHUNK_BYTES {
+            let mut ranges = Vec::new();
+            let mut start = 0;
+            while start < bytes.len() {
+                let end = start + parser.record_start(&bytes[start..], CHUNK_BYTES);
+                ranges.push(start..end);
+                start = end;
+            }
+
+            let parts: Vec<Vec<LogEntry>> = ranges
+                .into_par_iter()
+                .map(|r| {
+                    let mut out = Vec::with_capacity((r.end - r.start) / 80);
+                    parser.parse_chunk(&bytes[r], &mut out);
+                    out
+                })
+                .collect();
+            return parts.into_iter().flatten().collect();
+        }
+    }
+
+    let mut out = Vec::with_capacity(bytes.len() / 80);
+    parser.parse_chunk(bytes, &mut out);
+    out
+}
+
+/// Parse `input` with the parser registered under `parser` (name or alias).
+/// The `auto` preset detects the format from the start of `input`.
+pub fn parse(parser: &str, input: &str) -> Result<Vec<LogEntry>> {
+    let resolved = if parser == AUTO_PRESET {
+        detect(input.as_bytes(), detect::DEFAULT_SAMPLE_LINES)
+            .map(|d| d.parser)
+            .ok_or_else(|| anyhow!("Could not detect log format"))?
+    } else {
+        lookup(parser).ok_or_else(|| anyhow!("Unknown parser: {}", parser))?
+    };
+    resolved.parse(input)
 }
@@ -0,0 +1,569 @@
+//! Grouping of physical lines into multiline records, for formats where a
+//! stack trace, panic or wrapped message follows the line that logged it.
+//!
+//! A line joins the record before it when it matches the continuation
+//! pattern, or when a start pattern is set and the line does not match it.
+//! Records are capped at `max_lines`; the next line then starts a new one.
+
+use crate::config::LogEntry;
+use crate::parsers::LogParser;
+use memchr::{memchr, memchr_iter, memrchr};
+use regex::bytes::Regex;
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Default cap on the number of lines in one record.
+pub const DEFAULT_MAX_LINES: usize = 500;
+
+/// Continuation lines of Java, Go, Rust and Python stack traces: indented
+/// frames, `Caused by:` / `Suppressed:` sections, Go `goroutine` headers and
+/// function lines, Rust backtrace notes, and Python chaining separators.
+const STACK_TRACE_CONTINUATION: &str = concat!(
+    r"^(\s|$)",
+    r"|^(Caused by|Suppressed): ",
+    r"|^goroutine \d+ \[",
+    r"|^[\w./*()\-]+\(.*\)$",
+    r"|^created by ",
+    r"|^\[signal ",
+    r"|^stack backtrace:",
+    r"|^note: run with ",
+    r"|^Traceback \(most recent call last\):",
+    r"|^During handling of the above exception",
+    r"|^The above exception was the direct cause",
+    r"|^[A-Za-z_][\w.]*(Error|Exception|Exit|Interrupt)(: |$)",
+);
+
+/// Line predicate for rules that aren't a regex.
+type LineFn = Arc<dyn Fn(&[u8]) -> bool + Send + Sync>;
+
+#[derive(Clone)]
+enum Matcher {
+    Regex(Regex),
+    Fn(LineFn),
+}
+
+impl fmt::Debug for Matcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Matcher::Regex(re) => f.debug_tuple("Regex").field(&re.as_str()).finish(),
+            Matcher::Fn(_) => f.write_str("Fn"),
+        }
+    }
+}
+
+impl Matcher {
+    fn is_match(&self, line: &[u8]) -> bool {
+        match self {
+            Matcher::Regex(re) => re.is_match(line),
+            Matcher::Fn(f) => f(line),
+        }
+    }
+}
+
+/// Rules for grouping lines into records.
+#[derive(Debug, Clone)]
+pub struct Multiline {
+    start: Option<Matcher>,
+    continuation: Option<Matcher>,
+    max_lines: usize,
+    timeout: Option<Duration>,
+}
+
+impl Default for Multiline {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Multiline {
+    /// No patterns (every line is its own record) and [`DEFAULT_MAX_LINES`].
+    pub fn new() -> Self {
+        Self {
+            start: None,
+            continuation: None,
+            max_lines: DEFAULT_MAX_LINES,
+            timeout: None,
+        }
+    }
+
+    /// Continuation rules for common stack trace layouts.
+    pub fn stack_traces() -> Self {
+        Self::new()
+            .continuation(STACK_TRACE_CONTINUATION)
+            .expect("built-in stack trace pattern is valid")
+    }
+
+    /// Lines matching `pattern` start a new record; any other line continues
+    /// the current one.
+    pub fn start(mut self, pattern: &str) -> Result<Self, regex::Error> {
+        self.start = Some(Matcher::Regex(Regex::new(pattern)?));
+        Ok(self)
+    }
+
+    /// Like [`start`](Self::start), with a predicate instead of a regex.
+    pub fn start_with(mut self, is_start: impl Fn(&[u8]) -> bool + Send + Sync + 'static) -> Self {
+        self.start = Some(Matcher::Fn(Arc::new(is_start)));
+        self
+    }
+
+    /// Lines matching `pattern` always continue the current record.
+    pub fn continuation(mut self, pattern: &str) -> Result<Self, regex::Error> {
+        self.continuation = Some(Matcher::Regex(Regex::new(pattern)?));
+        Ok(self)
+    }
+
+    /// Like [`continuation`](Self::continuation), with a predicate.
+    pub fn continuation_with(
+        mut self,
+        continues: impl Fn(&[u8]) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.continuation = Some(Matcher::Fn(Arc::new(continues)));
+        self
+    }
+
+    /// Maximum number of lines in one record (at least 1).
+    pub fn max_lines(mut self, max_lines: usize) -> Self {
+        self.max_lines = max_lines.max(1);
+        self
+    }
+
+    /// For line-at-a-time input ([`Combiner`]): flush a pending record once
+    /// no line has arrived for `timeout`. Grouping whole input
+    /// ([`for_each_record`](Self::for_each_record), [`MultilineParser`])
+    /// ignores it.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Also continue a record wherever `other` would. The start and
+    /// continuation patterns of both are kept; the limits are this one's.
+    pub fn or(self, other: Multiline) -> Self {
+        let own = self.clone();
+        Self {
+            start: None,
+            continuation: Some(Matcher::Fn(Arc::new(move |line| {
+                own.continues(line) || other.continues(line)
+            }))),
+            ..self
+        }
+    }
+
+    /// Whether `line` belongs to the record before it (ignoring `max_lines`).
+    pub fn continues(&self, line: &[u8]) -> bool {
+        if let Some(continuation) = &self.continuation
+            && continuation.is_match(line)
+        {
+            return true;
+        }
+        self.start
+            .as_ref()
+            .is_some_and(|start| !start.is_match(line))
+    }
+
+    /// Offset of the first line at or after `pos` that starts a record, or
+    /// `bytes.len()`. `bytes` must start at a record. Splitting input only at
+    /// these offsets keeps every record in one piece, and groups lines the
+    /// same way [`for_each_record`](Self::for_each_record) does on the whole.
+    pub fn record_start(&self, bytes: &[u8], pos: usize) -> usize {
+        if pos == 0 {
+            return 0;
+        }
+        let Some(nl) = bytes.get(pos - 1..).and_then(|rest| memchr(b'\n', rest)) else {
+            return bytes.len();
+        };
+        let target = pos + nl;
+
+        // Back up to the last line that can only start a record, then count
+        // lines from there so records are cut at `max_lines` as in
+        // `for_each_record`.
+        let mut head = target;
+        while head > 0 {
+            let end = head - 1;
+            head = memrchr(b'\n', &bytes[..end]).map_or(0, |i| i + 1);
+            if !self.continues(trim_cr(&bytes[head..end])) {
+                break;
+            }
+        }
+
+        let mut lines = 0;
+        let mut start = head;
+        while start < bytes.len() {
+            let end = memchr(b'\n', &bytes[start..]).map_or(bytes.len(), |i| start + i);
+            if lines > 0 && lines < self.max_lines && self.continues(trim_cr(&bytes[start..end])) {
+                lines += 1;
+            } else if start >= target {
+                return start;
+            } else {
+                lines = 1;
+            }
+            start = end + 1;
+        }
+        bytes.len()
+    }
+
+    /// Group the lines of `bytes` into records and call `f` with each
+    /// record's first line and its continuation lines (`\r` stripped).
+    pub fn for_each_record<'a>(&self, bytes: &'a [u8], mut f: impl FnMut(&'a [u8], &[&'a [u8]])) {
+        let mut first: Option<&[u8]> = None;
+        let mut rest: Vec<&[u8]> = Vec::new();
+        let mut start = 0;
+
+        let ends =
+            memchr_iter(b'\n', bytes).chain((!bytes.ends_with(b"\n")).then_some(bytes.len()));
+        for end in ends {
+            let line = trim_cr(&bytes[start..end]);
+            start = end + 1;
+
+            if let Some(head) = first {
+                if rest.len() + 1 < self.max_lines && self.continues(line) {
+                    rest.push(line);
+                    continue;
+                }
+                f(head, &rest);
+                rest.clear();
+            }
+            first = Some(line);
+        }
+        if let Some(head) = first {
+            f(head, &rest);
+        }
+    }
+}
+
+fn trim_cr(line: &[u8]) -> &[u8] {
+    line.strip_suffix(b"\r").unwrap_or(line)
+}
+
+/// Append continuation lines to an entry's message, one per line.
+pub fn append_lines(entry: &mut LogEntry, lines: &[&[u8]]) {
+    if lines.is_empty() {
+        return;
+    }
+    let message = entry.message.get_or_insert_with(String::new);
+    for line in lines {
+        message.push('\n');
+        message.push_str(&String::from_utf8_lossy(line));
+    }
+}
+
+/// Incremental grouping for line-at-a-time input such as a followed file:
+/// records are returned as soon as the next record starts, or once the
+/// rules' timeout has passed without a new line.
+pub struct Combiner<'a> {
+    rules: &'a Multiline,
+    lines: Vec<Vec<u8>>,
+    last_line: Option<Instant>,
+}
+
+impl<'a> Combiner<'a> {
+    pub fn new(rules: &'a Multiline) -> Self {
+        Self {
+            rules,
+            lines: Vec::new(),
+            last_line: None,
+        }
+    }
+
+    /// Add a line (without its newline) received at `now`. Returns the
+    /// previous record if it is now complete.
+    pub fn push(&mut self, line: &[u8], now: Instant) -> Option<Vec<Vec<u8>>> {
+        let mut done = self.poll(now);
+        if !self.lines.is_empty()
+            && (self.lines.len() >= self.rules.max_lines || !self.rules.continues(line))
+        {
+            done = self.finish();
+        }
+        self.lines.push(line.to_vec());
+        self.last_line = Some(now);
+        done
+    }
+
+    /// Flush the pending record if the timeout has passed since its last line.
+    pub fn poll(&mut self, now: Instant) -> Option<Vec<Vec<u8>>> {
+        let timeout = self.rules.timeout?;
+        let last = self.last_line?;
+        if now.saturating_duration_since(last) >= timeout {
+            self.finish()
+        } else {
+            None
+        }
+    }
+
+    /// Flush the pending record, if any.
+    pub fn finish(&mut self) -> Option<Vec<Vec<u8>>> {
+        self.last_line = None;
+        (!self.lines.is_empty()).then(|| std::mem::take(&mut self.lines))
+    }
+}
+
+/// Adds multiline grouping to another parser, e.g. to fold stack traces in
+/// an otherwise line-oriented format. The rules are combined with the inner
+/// parser's own [`multiline`](LogParser::multiline) rules. For a parser that
+/// groups lines by itself ([`groups_lines`](LogParser::groups_lines)), its
+/// records are kept and the rules are matched against their messages, so a
+/// record continuing the one before is folded into its message.
+pub struct MultilineParser {
+    inner: Arc<dyn LogParser>,
+    rules: Multiline,
+}
+
+impl MultilineParser {
+    pub fn new(inner: Arc<dyn LogParser>, rules: Multiline) -> Self {
+        let rules = match inner.multiline() {
+            Some(own) => rules.or(own.clone()),
+            None => rules,
+        };
+        Self { inner, rules }
+    }
+
+    /// Whether `entry` continues the record before it.
+    fn continues(&self, entry: &LogEntry) -> bool {
+        entry
+            .message
+            .as_deref()
+            .is_some_and(|message| self.rules.continues(message.as_bytes()))
+    }
+}
+
+impl LogParser for MultilineParser {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    fn aliases(&self) -> &[&str] {
+        self.inner.aliases()
+    }
+
+    fn description(&self) -> &str {
+        self.inner.description()
+    }
+
+    fn parse_line(&self, line: &[u8]) -> Option<LogEntry> {
+        self.inner.parse_line(line)
+    }
+
+    fn multiline(&self) -> Option<&Multiline> {
+        (!self.inner.groups_lines()).then_some(&self.rules)
+    }
+
+    fn parse_record(&self, first: &[u8], continuation: &[&[u8]]) -> Option<LogEntry> {
+        self.inner.parse_record(first, continuation)
+    }
+
+    fn parse_chunk(&self, bytes: &[u8], out: &mut Vec<LogEntry>) {
+        if !self.inner.groups_lines() {
+            self.rules.for_each_record(bytes, |first, rest| {
+                if let Some(entry) = self.inner.parse_record(first, rest) {
+                    out.push(entry);
+                }
+            });
+            return;
+        }
+
+        let mut entries = Vec::new();
+        self.inner.parse_chunk(bytes, &mut entries);
+        let mut head: Option<LogEntry> = None;
+        let mut lines = 0;
+        for entry in entries {
+            if let Some(head) = head.as_mut()
+                && lines < self.rules.max_lines
+                && self.continues(&entry)
+            {
+                let message = entry.message.unwrap_or_default();
+                append_lines(head, &[message.as_bytes()]);
+                lines += 1;
+                continue;
+            }
+            out.extend(head.replace(entry));
+            lines = 1;
+        }
+        out.extend(head);
+    }
+
+    /// The inner parser's record boundaries, skipping those where the next
+    /// record would be folded into the one before.
+    fn record_start(&self, bytes: &[u8], pos: usize) -> usize {
+        if !self.inner.groups_lines() {
+            return self.rules.record_start(bytes, pos);
+        }
+        if pos == 0 {
+            return 0;
+        }
+        let mut start = self.inner.record_start(bytes, pos);
+        let mut entries = Vec::new();
+        while start < bytes.len() {
+            let next = self.inner.record_start(bytes, start + 1);
+            entries.clear();
+            self.inner.parse_chunk(&bytes[start..next], &mut entries);
+            match entries.first() {
+                Some(entry) if !self.continues(entry) => return start,
+                _ => start = next,
+            }
+        }
+        bytes.len()
+    }
+
+    fn groups_lines(&self) -> bool {
+        self.inner.groups_lines()
+    }
+
+    fn sniff(&self, line: &[u8]) -> bool {
+        self.inner.sniff(line)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsers::container::ContainerParser;
+    use crate::parsers::journald::JournalExportParser;
+
+    const JAVA: &[u8] = b"2025-08-31 22:50:01 ERROR request failed\r
+java.lang.IllegalStateException: boom
+\tat com.example.Foo.bar(Foo.java:10)
+Caused by: java.io.IOException: closed
+\t... 3 more
+2025-08-31 22:50:02 INFO next
+";
+
+    #[test]
+    fn groups_lines_into_records() {
+        let records = |rules: &Multiline, input: &'static [u8]| {
+            let mut records = Vec::new();
+            rules.for_each_record(input, |first, rest| records.push((first, rest.len())));
+            records
+        };
+
+        let by_start = Multiline::new().start(r"^\d{4}-\d{2}-\d{2} ").unwrap();
+        let grouped = records(&by_start, JAVA);
+        assert_eq!(grouped.len(), 2);
+        assert_eq!(
+            grouped[0],
+            (&b"2025-08-31 22:50:01 ERROR request failed"[..], 4)
+        );
+        let next = JAVA.len() - b"2025-08-31 22:50:02 INFO next\n".len();
+        assert_eq!(by_start.record_start(JAVA, 50), next);
+
+        let traces = Multiline::stack_traces();
+        assert!(traces.continues(b"goroutine 1 [running]:"));
+        assert!(traces.continues(b"main.main()"));
+        assert!(!traces.continues(b"2025-08-31 22:50:02 INFO next"));
+        assert_eq!(records(&traces, JAVA).len(), 2);
+
+        let capped = by_start.clone().max_lines(2);
+        assert_eq!(records(&capped, JAVA).len(), 4);
+        let third = memchr::memmem::find(JAVA, b"\tat ").unwrap();
+        assert_eq!(capped.record_start(JAVA, 45), third);
+
+        let rules = Multiline::stack_traces().timeout(Duration::from_millis(100));
+        let mut combiner = Combiner::new(&rules);
+        let t0 = Instant::now();
+        assert!(combiner.push(b"panic: oops", t0).is_none());
+        assert!(combiner.push(b"goroutine 1 [running]:", t0).is_none());
+        assert!(combiner.poll(t0 + Duration::from_millis(50)).is_none());
+        let flushed = combiner.poll(t0 + Duration::from_millis(150)).unwrap();
+        assert_eq!(flushed.len(), 2);
+        assert!(combiner.push(b"next", t0).is_none());
+        assert_eq!(combiner.finish(), Some(vec![b"next".to_vec()]));
+    }
+
+    #[test]
+    fn split_input_groups_like_whole_input() {
+        let traces = Multiline::stack_traces().max_lines(2);
+        let by_start = Multiline::new()
+            .start(r"^\d{4}-\d{2}-\d{2} ")
+            .unwrap()
+            .max_lines(3);
+        for rules in [traces, by_start] {
+            let mut whole = Vec::new();
+            rules.for_each_record(JAVA, |first, rest| whole.push((first, rest.to_vec())));
+
+            for pos in 0..=JAVA.len() {
+                let cut = rules.record_start(JAVA, pos);
+                let mut split = Vec::new();
+                for piece in [&JAVA[..cut], &JAVA[cut..]]
+                    .into_iter()
+                    .filter(|p| !p.is_empty())
+                {
+                    rules.for_each_record(piece, |first, rest| split.push((first, rest.to_vec())));
+                }
+                assert_eq!(split, whole, "cut at {cut} for pos {pos}");
+            }
+        }
+    }
+
+    #[test]
+    fn wrapped_parsers_keep_their_own_records() {
+        let docker = concat!(
+            r#"{"log":"part one ","stream":"stdout","time":"2025-05-12T06:25:24.1Z"}"#,
+            "\n",
+            r#"{"log":"part two\n","stream":"stdout","time":"2025-05-12T06:25:24.2Z"}"#,
+            "\n",
+            r#"{"log":"request failed\n","stream":"stderr","time":"2025-05-12T06:25:24.3Z"}"#,
+            "\n",
+            r#"{"log":"java.io.IOException: closed\n","stream":"stderr","time":"2025-05-12T06:25:24.3Z"}"#,
+            "\n",
+            r#"{"log":"\tat com.example.Foo.bar(Foo.java:10)\n","stream":"stderr","time":"2025-05-12T06:25:24.3Z"}"#,
+            "\n",
+            r#"{"log":"next\n","stream":"stdout","time":"2025-05-12T06:25:24.4Z"}"#,
+            "\n",
+        )
+        .as_bytes();
+        let export = b"MESSAGE=panic: oops\n\nMESSAGE=goroutine 1 [running]:\n\n\
+                       MESSAGE=main.main()\n\nMESSAGE=next\n\n";
+
+        let parser = MultilineParser::new(
+            Arc::new(ContainerParser::docker()),
+            Multiline::stack_traces(),
+        );
+        let messages = |v: Vec<LogEntry>| {
+            v.into_iter()
+                .map(|e| e.message.unwrap())
+                .collect::<Vec<_>>()
+        };
+        let v = parser.parse_bytes(docker).unwrap();
+        assert_eq!(
+            messages(v),
+            [
+                "part one part two",
+                "request failed\njava.io.IOException: closed\n\tat com.example.Foo.bar(Foo.java:10)",
+                "next"
+            ]
+        );
+        let line = |n| {
+            docker
+                .split(|b| *b == b'\n')
+                .take(n)
+                .map(|l| l.len() + 1)
+                .sum::<usize>()
+        };
+        // neither a partial piece nor a trace is split
+        assert_eq!(parser.record_start(docker, 1), line(2));
+        assert_eq!(parser.record_start(docker, line(3)), line(5));
+
+        let journal =
+            MultilineParser::new(Arc::new(JournalExportParser), Multiline::stack_traces());
+        let v = journal.parse_bytes(export).unwrap();
+        assert_eq!(
+            messages(v),
+            ["panic: oops\ngoroutine 1 [running]:\nmain.main()", "next"]
+        );
+        assert_eq!(
+            journal.record_start(export, 1),
+            export.len() - b"MESSAGE=next\n\n".len()
+        );
+
+        for (parser, input) in [(&parser, docker), (&journal, &export[..])] {
+            let whole = messages(parser.parse_bytes(input).unwrap());
+            for pos in 0..=input.len() {
+                let cut = parser.record_start(input, pos);
+                let mut split = Vec::new();
+                parser.parse_chunk(&input[..cut], &mut split);
+                parser.parse_chunk(&input[cut..], &mut split);
+                assert_eq!(messages(split), whole, "cut at {cut} for pos {pos}");
+            }
+        }
+    }
+}
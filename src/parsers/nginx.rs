@@ -1,85 +1,44 @@
 use anyhow::Result;
 use memchr::memchr_iter;
-use std::ops::Range;
 
-use crate::config::LogEntry;
-
-#[cfg(feature = "parallel")]
-use rayon::prelude::*;
+use crate::config::{HttpRequest, LogEntry};
+use crate::parsers::access::{DurationUnit, has_clf_timestamp, http_request};
+use crate::parsers::{LogParser, parse_chunked};
+use crate::severity::Severity;
 
 /// Tunables
-const CHUNK_BYTES: usize = 4 * 1024 * 1024; // 4MB chunk default (tune to CPU/cache)
 const MIN_LINE_LEN: usize = 20; // skip tiny lines
 
-// constant strings for service/levels
+// constant strings for service
 static SERVICE_NGINX: &str = "nginx";
-static LEVEL_INFO: &str = "info";
-static LEVEL_WARN: &str = "warn";
-static LEVEL_ERROR: &str = "error";
 
 pub fn parse_nginx(input: &str) -> Result<Vec<LogEntry>> {
-    // Fast path: if parallel feature enabled and input large -> parallel
-    #[cfg(feature = "parallel")]
-    {
-        if input.len() > CHUNK_BYTES {
-            return Ok(parse_parallel(input));
-        }
-    }
-
-    // Single-thread fallback
-    Ok(parse_single(input.as_bytes()))
+    Ok(parse_chunked(&NginxParser, input.as_bytes()))
 }
 
-#[cfg(feature = "parallel")]
-fn parse_parallel(input: &str) -> Vec<LogEntry> {
-    let bytes = input.as_bytes();
-    let len = bytes.len();
+/// Registry adapter for [`parse_nginx`].
+pub struct NginxParser;
 
-    // build ranges aligned to newline boundaries
-    let mut ranges = Vec::<Range<usize>>::new();
-    let mut start = 0usize;
+impl LogParser for NginxParser {
+    fn name(&self) -> &str {
+        "nginx"
+    }
 
-    while start < len {
-        let mut end = (start + CHUNK_BYTES).min(len);
-        if end < len {
-            // advance end to include the rest of the current line
-            while end < len && bytes[end] != b'\n' {
-                end += 1;
-            }
-            if end < len {
-                end += 1; // include newline
-            }
-        } else {
-            end = len;
-        }
-        ranges.push(start..end);
-        start = end;
-    }
-
-    // Parse each range in parallel, each returns Vec<LogEntry>
-    let parts: Vec<Vec<LogEntry>> = ranges
-        .into_par_iter()
-        .map(|r| {
-            let approx_lines = (r.end - r.start) / 80;
-            let mut out = Vec::with_capacity(approx_lines);
-            parse_chunk_into_vec(&bytes[r], &mut out);
-            out
-        })
-        .collect();
-
-    // flatten vectors (preserves grouping)
-    let total: usize = parts.iter().map(Vec::len).sum();
-    let mut result = Vec::with_capacity(total);
-    for mut p in parts {
-        result.append(&mut p);
-    }
-    result
-}
+    fn description(&self) -> &str {
+        "Nginx access logs (combined format) and error logs"
+    }
 
-fn parse_single(bytes: &[u8]) -> Vec<LogEntry> {
-    let mut out = Vec::with_capacity(bytes.len() / 80);
-    parse_chunk_into_vec(bytes, &mut out);
-    out
+    fn parse_line(&self, line: &[u8]) -> Option<LogEntry> {
+        parse_line_to_logentry(line)
+    }
+
+    fn parse_chunk(&self, bytes: &[u8], out: &mut Vec<LogEntry>) {
+        parse_chunk_into_vec(bytes, out)
+    }
+
+    fn sniff(&self, line: &[u8]) -> bool {
+        (has_clf_timestamp(line) || is_error_line(line)) && parse_line_to_logentry(line).is_some()
+    }
 }
 
 /// Parse a chunk (doesn't contain split lines) and push LogEntry into `out`.
@@ -87,27 +46,30 @@ fn parse_chunk_into_vec(bytes: &[u8], out: &mut Vec<LogEntry>) {
     let mut start = 0usize;
     for nl in memchr_iter(b'\n', bytes) {
         let line = &bytes[start..nl];
-        if line.len() >= MIN_LINE_LEN {
-            if let Some(entry) = parse_line_to_logentry(line) {
-                out.push(entry);
-            }
+        if line.len() >= MIN_LINE_LEN
+            && let Some(entry) = parse_line_to_logentry(line)
+        {
+            out.push(entry);
         }
         start = nl + 1;
     }
     if start < bytes.len() {
         let line = &bytes[start..];
-        if line.len() >= MIN_LINE_LEN {
-            if let Some(entry) = parse_line_to_logentry(line) {
-                out.push(entry);
-            }
+        if line.len() >= MIN_LINE_LEN
+            && let Some(entry) = parse_line_to_logentry(line)
+        {
+            out.push(entry);
         }
     }
 }
 
 /// Parse single log line (byte slice). Returns owned `LogEntry`.
-/// Assumes original input was valid UTF-8 (we use unchecked conversions).
+/// Fields are checked for UTF-8 as they are sliced out; invalid UTF-8 in
+/// any of them rejects the line.
 fn parse_line_to_logentry(line: &[u8]) -> Option<LogEntry> {
-    let s = unsafe { std::str::from_utf8_unchecked(line) };
+    if is_error_line(line) {
+        return parse_error_line(line);
+    }
     let bytes = line;
     let len = bytes.len();
 
@@ -134,14 +96,15 @@ fn parse_line_to_logentry(line: &[u8]) -> Option<LogEntry> {
     }
 
     let ip_end = ip_end?;
-    let ts_start = ts_start?;
+    // the timestamp follows the client address
+    let ts_start = ts_start.filter(|&ts| ts > ip_end)?;
     let ts_end = ts_end?;
     let req_start = req_start?;
     let req_end = req_end?;
 
-    let ip = unsafe { std::str::from_utf8_unchecked(&bytes[..ip_end]) };
-    let timestamp = unsafe { std::str::from_utf8_unchecked(&bytes[ts_start..ts_end]) };
-    let request = unsafe { std::str::from_utf8_unchecked(&bytes[req_start..req_end]) };
+    let ip = std::str::from_utf8(&bytes[..ip_end]).ok()?;
+    let timestamp = std::str::from_utf8(&bytes[ts_start..ts_end]).ok()?;
+    let request = std::str::from_utf8(&bytes[req_start..req_end]).ok()?;
 
     // parse status three-digit after request closing quote
     let mut status_start = req_end + 1;
@@ -152,15 +115,9 @@ fn parse_line_to_logentry(line: &[u8]) -> Option<LogEntry> {
         return None;
     }
     let status_slice = &bytes[status_start..status_start + 3];
-    let status = unsafe { std::str::from_utf8_unchecked(status_slice) };
+    let status = std::str::from_utf8(status_slice).ok()?;
     let status_num = fast_parse_status(status)?;
-    let level_static = if (400..500).contains(&status_num) {
-        LEVEL_WARN
-    } else if status_num >= 500 {
-        LEVEL_ERROR
-    } else {
-        LEVEL_INFO
-    };
+    let level = Severity::from_http_status(status_num as u16);
 
     let (method, path) = if let Some(space_idx) = request.find(' ') {
         let method = &request[..space_idx];
@@ -182,8 +139,106 @@ fn parse_line_to_logentry(line: &[u8]) -> Option<LogEntry> {
         timestamp: Some(timestamp.to_string()),
         host: Some(ip.to_string()),
         service: Some(SERVICE_NGINX.to_string()),
-        level: Some(level_static.to_string()),
+        level: Some(level),
         message: Some(msg),
+        http: Some(http_request(
+            &bytes[ip_end..ts_start - 1],
+            request,
+            status_num as u16,
+            &bytes[status_start + 3..],
+            DurationUnit::Seconds,
+        )),
+        ..Default::default()
+    })
+}
+
+/// `2025/05/12 06:25:24 [level] ...`, the error log layout.
+fn is_error_line(line: &[u8]) -> bool {
+    line.len() > 21
+        && line[4] == b'/'
+        && line[7] == b'/'
+        && line[10] == b' '
+        && line[13] == b':'
+        && line[19] == b' '
+        && line[20] == b'['
+}
+
+/// Parse an error log line:
+///
+/// ```text
+/// 2025/05/12 06:25:24 [error] 1234#0: *5 upstream timed out ..., client: 1.2.3.4,
+///     server: x, request: "GET / HTTP/1.1", upstream: "http://...", host: "x"
+/// ```
+///
+/// `pid#tid` and the `*connection` id become fields; the trailing
+/// `key: value` context becomes the host (client), `http` (request,
+/// referrer) and `server`/`upstream`/`http_host` fields.
+fn parse_error_line(line: &[u8]) -> Option<LogEntry> {
+    let s = std::str::from_utf8(line).ok()?.trim_end();
+    let timestamp = &s[..19];
+    let (level, rest) = s[21..].split_once("] ")?;
+    let level = level.parse::<Severity>().ok()?;
+
+    let mut entry = LogEntry {
+        timestamp: Some(timestamp.to_string()),
+        service: Some(SERVICE_NGINX.to_string()),
+        level: Some(level),
+        ..Default::default()
+    };
+
+    // 1234#0: *5 message
+    let (pid, rest) = rest.split_once('#')?;
+    let (tid, mut rest) = rest.split_once(": ")?;
+    entry.set_field("pid", pid.parse::<i64>().ok()?);
+    entry.set_field("tid", tid.parse::<i64>().ok()?);
+    if let Some(conn) = rest.strip_prefix('*')
+        && let Some((id, message)) = conn.split_once(' ')
+        && let Ok(id) = id.parse::<i64>()
+    {
+        entry.set_field("connection", id);
+        rest = message;
+    }
+
+    let (message, context) = match rest.find(", client: ") {
+        Some(at) => (&rest[..at], &rest[at + 2..]),
+        None => (rest, ""),
+    };
+    entry.message = Some(message.to_string());
+
+    let mut http = HttpRequest::default();
+    for (key, value) in error_context(context) {
+        match key {
+            "client" => entry.host = Some(value.to_string()),
+            "request" => {
+                let mut parts = value.splitn(3, ' ');
+                http.method = parts.next().map(str::to_string);
+                http.path = parts.next().map(str::to_string);
+                http.protocol = parts.next().map(str::to_string);
+            }
+            "referrer" => http.referrer = Some(value.to_string()),
+            "host" => entry.set_field("http_host", value),
+            other => entry.set_field(other, value),
+        }
+    }
+    if http != HttpRequest::default() {
+        entry.http = Some(http);
+    }
+    Some(entry)
+}
+
+/// `key: value, key: "quoted, value", ...` pairs after the message.
+fn error_context(mut s: &str) -> impl Iterator<Item = (&str, &str)> {
+    std::iter::from_fn(move || {
+        let (key, rest) = s.split_once(": ")?;
+        let (value, next) = match rest.strip_prefix('"') {
+            Some(quoted) => {
+                let end = quoted.find('"')?;
+                (&quoted[..end], &quoted[end + 1..])
+            }
+            None => rest.split_once(", ").map_or((rest, ""), |(v, n)| (v, n)),
+        };
+        s = next.strip_prefix(", ").unwrap_or(next);
+        Some((key, value))
     })
 }
 
@@ -205,6 +260,7 @@ fn fast_parse_status(status_str: &str) -> Option<usize> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::FieldValue;
 
     const SAMPLE: &str = r#"127.0.0.1 - - [12/May/2025:06:25:24 +0000] "GET /index.html HTTP/1.1" 200 612 "-" "curl/7.68.0"
 192.168.1.10 - - [12/May/2025:06:25:25 +0000] "POST /api/v1/data HTTP/1.1" 500 12 "-" "UA"
@@ -215,12 +271,76 @@ mod tests {
         let v = parse_nginx(SAMPLE).unwrap();
         assert_eq!(v.len(), 2);
         assert_eq!(v[0].host.as_deref(), Some("127.0.0.1"));
-        assert_eq!(v[0].level.as_deref(), Some("info"));
-        assert_eq!(v[1].level.as_deref(), Some("error"));
-        assert!(v[1]
-            .message
-            .as_deref()
-            .unwrap()
-            .contains("POST /api/v1/data -> 500"));
+        assert_eq!(v[0].level, Some(Severity::Info));
+        assert_eq!(v[1].level, Some(Severity::Error));
+        assert!(
+            v[1].message
+                .as_deref()
+                .unwrap()
+                .contains("POST /api/v1/data -> 500")
+        );
+
+        let http = v[0].http.as_ref().unwrap();
+        assert_eq!(http.status, Some(200));
+        assert_eq!(http.bytes, Some(612));
+        assert_eq!(http.user_agent.as_deref(), Some("curl/7.68.0"));
+        assert_eq!(http.remote_user, None);
+    }
+
+    #[test]
+    fn parses_error_lines() {
+        let error = concat!(
+            "2025/05/12 06:25:24 [error] 1234#0: *5 upstream timed out (110: Connection timed out) ",
+            "while reading response header from upstream, client: 1.2.3.4, server: example.com, ",
+            "request: \"GET /api?a=1 HTTP/1.1\", upstream: \"http://127.0.0.1:8080/api?a=1\", ",
+            "host: \"example.com\"\n",
+            "2025/05/12 06:25:25 [notice] 1#1: signal process started\n",
+        );
+        let v = parse_nginx(error).unwrap();
+        assert_eq!(v.len(), 2);
+        assert_eq!(v[0].timestamp.as_deref(), Some("2025/05/12 06:25:24"));
+        assert_eq!(v[0].level, Some(Severity::Error));
+        assert_eq!(v[0].host.as_deref(), Some("1.2.3.4"));
+        assert_eq!(
+            v[0].message.as_deref(),
+            Some(
+                "upstream timed out (110: Connection timed out) while reading response header from upstream"
+            )
+        );
+        assert_eq!(v[0].fields.get("pid"), Some(&FieldValue::Int(1234)));
+        assert_eq!(v[0].fields.get("tid"), Some(&FieldValue::Int(0)));
+        assert_eq!(v[0].fields.get("connection"), Some(&FieldValue::Int(5)));
+        assert_eq!(
+            v[0].fields.get("server"),
+            Some(&FieldValue::from("example.com"))
+        );
+        assert_eq!(
+            v[0].fields.get("upstream"),
+            Some(&FieldValue::from("http://127.0.0.1:8080/api?a=1"))
+        );
+        assert_eq!(
+            v[0].fields.get("http_host"),
+            Some(&FieldValue::from("example.com"))
+        );
+        let http = v[0].http.as_ref().unwrap();
+        assert_eq!(http.method.as_deref(), Some("GET"));
+        assert_eq!(http.path.as_deref(), Some("/api?a=1"));
+        assert_eq!(v[1].level, Some(Severity::Notice));
+        assert_eq!(v[1].message.as_deref(), Some("signal process started"));
+        assert!(NginxParser.sniff(error.as_bytes()));
+    }
+
+    #[test]
+    fn rejects_error_lines_without_a_known_level_or_pid() {
+        assert!(
+            NginxParser
+                .parse_line(b"2025/05/12 06:25:24 [bogus] 1#1: message")
+                .is_none()
+        );
+        assert!(
+            NginxParser
+                .parse_line(b"2025/05/12 06:25:24 [error] 1234")
+                .is_none()
+        );
     }
 }
@@ -0,0 +1,436 @@
+//! Python `logging` output in any `%`-style `logging.Formatter` layout, e.g.
+//! `%(asctime)s [%(levelname)s] %(name)s:%(lineno)d - %(message)s`.
+//!
+//! The format string is compiled once into literals and fields that are
+//! matched left to right: text fields end at the next literal, numeric ones
+//! at the end of their digits. Lines that don't match the format continue
+//! the previous record, so tracebacks are folded in as with `python_web`.
+
+use crate::config::{FieldValue, LogEntry};
+use crate::parsers::LogParser;
+use crate::parsers::multiline::Multiline;
+use crate::parsers::python_web;
+use crate::severity::Severity;
+use anyhow::{Result, anyhow, bail};
+use chrono::{DateTime, SecondsFormat};
+use memchr::memmem;
+use std::sync::Arc;
+
+/// A compiled `logging.Formatter` format string.
+#[derive(Debug)]
+pub struct PythonFormat {
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug)]
+enum Segment {
+    Literal(String),
+    Field(Field),
+}
+
+#[derive(Debug)]
+struct Field {
+    attr: Attr,
+    kind: Kind,
+}
+
+/// `LogRecord` attributes with a core [`LogEntry`] field; everything else
+/// (`lineno`, `funcName`, `process`, `thread`, custom attributes from
+/// filters or `extra=`, ...) is kept in `fields` under its own name.
+#[derive(Debug, PartialEq)]
+enum Attr {
+    AscTime,
+    Created,
+    Msecs,
+    LevelName,
+    LevelNo,
+    Name,
+    Message,
+    Other(String),
+}
+
+/// Value shape, from the `%` conversion type.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kind {
+    Text,
+    Int,
+    Float,
+}
+
+impl PythonFormat {
+    /// Compile a `%`-style format string. `%%` is a literal percent sign.
+    pub fn new(format: &str) -> Result<Self> {
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut rest = format;
+
+        while let Some(pct) = rest.find('%') {
+            literal.push_str(&rest[..pct]);
+            rest = &rest[pct + 1..];
+            if let Some(after) = rest.strip_prefix('%') {
+                literal.push('%');
+                rest = after;
+                continue;
+            }
+
+            let (name, spec) = rest
+                .strip_prefix('(')
+                .and_then(|r| r.split_once(')'))
+                .ok_or_else(|| anyhow!("expected %(name) in format at '%{}'", rest))?;
+            // flags, width and precision, then the conversion type
+            let conv_at = spec
+                .find(|c: char| !matches!(c, '-' | '+' | ' ' | '#' | '.' | '0'..='9'))
+                .ok_or_else(|| anyhow!("missing conversion type after %({})", name))?;
+            let kind = match spec[conv_at..].chars().next() {
+                Some('s' | 'r' | 'a') => Kind::Text,
+                Some('d' | 'i' | 'u') => Kind::Int,
+                Some('f' | 'F' | 'e' | 'E' | 'g' | 'G') => Kind::Float,
+                Some(c) => bail!("unsupported conversion '%{}' for %({})", c, name),
+                None => unreachable!(),
+            };
+            rest = &spec[conv_at + 1..];
+
+            if !literal.is_empty() {
+                segments.push(Segment::Literal(std::mem::take(&mut literal)));
+            } else if let Some(Segment::Field(prev)) = segments.last()
+                && prev.kind == Kind::Text
+            {
+                bail!("%({}) must be separated from the field before it", name);
+            }
+            segments.push(Segment::Field(Field {
+                attr: Attr::from_name(name),
+                kind,
+            }));
+        }
+        literal.push_str(rest);
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+
+        if !segments.iter().any(|s| matches!(s, Segment::Field(_))) {
+            bail!("format has no %(name) fields");
+        }
+        Ok(Self { segments })
+    }
+
+    /// Match a line against the format.
+    pub fn parse_line(&self, line: &str) -> Option<LogEntry> {
+        let mut entry = LogEntry::default();
+        self.walk(line, |field, value| field.apply(&mut entry, value))
+            .then_some(entry)
+    }
+
+    /// Whether `line` matches the format, without building the entry.
+    pub fn is_match(&self, line: &str) -> bool {
+        self.walk(line, |field, value| field.accepts(value).then_some(()))
+    }
+
+    /// Match the segments left to right, handing each field's trimmed value
+    /// to `on_field`. False if a literal or value doesn't match.
+    fn walk<'a>(
+        &self,
+        line: &'a str,
+        mut on_field: impl FnMut(&Field, &'a str) -> Option<()>,
+    ) -> bool {
+        let mut pos = 0;
+
+        for (i, segment) in self.segments.iter().enumerate() {
+            let rest = &line[pos..];
+            match segment {
+                Segment::Literal(lit) => {
+                    if !rest.starts_with(lit.as_str()) {
+                        return false;
+                    }
+                    pos += lit.len();
+                }
+                Segment::Field(field) => {
+                    let len = match (field.kind, self.segments.get(i + 1)) {
+                        (Kind::Int | Kind::Float, _) => number_len(rest, field.kind),
+                        (Kind::Text, Some(Segment::Literal(lit))) => field.text_len(rest, lit),
+                        (Kind::Text, _) => Some(rest.len()),
+                    };
+                    let Some(len) = len else {
+                        return false;
+                    };
+                    if on_field(field, rest[..len].trim()).is_none() {
+                        return false;
+                    }
+                    pos += len;
+                }
+            }
+        }
+
+        line[pos..].trim_end().is_empty()
+    }
+}
+
+impl Attr {
+    fn from_name(name: &str) -> Self {
+        match name {
+            "asctime" => Attr::AscTime,
+            "created" => Attr::Created,
+            "msecs" => Attr::Msecs,
+            "levelname" => Attr::LevelName,
+            "levelno" => Attr::LevelNo,
+            "name" => Attr::Name,
+            "message" => Attr::Message,
+            other => Attr::Other(other.to_string()),
+        }
+    }
+}
+
+impl Field {
+    /// Length of a text value followed by the literal `lit`. Messages take
+    /// the last occurrence of `lit`, everything else the first; `asctime`
+    /// extends past spaces until it has a time of day (`%Y-%m-%d %H:%M:%S`,
+    /// `%b %d %H:%M:%S`).
+    fn text_len(&self, rest: &str, lit: &str) -> Option<usize> {
+        match self.attr {
+            Attr::Message => memmem::rfind(rest.as_bytes(), lit.as_bytes()),
+            Attr::AscTime => {
+                let mut end = memmem::find(rest.as_bytes(), lit.as_bytes())?;
+                while !rest[..end].contains(':')
+                    && lit.starts_with(' ')
+                    && rest.as_bytes().get(end + 1).is_some_and(u8::is_ascii_digit)
+                {
+                    end += 1 + memmem::find(&rest.as_bytes()[end + 1..], lit.as_bytes())?;
+                }
+                Some(end)
+            }
+            _ => memmem::find(rest.as_bytes(), lit.as_bytes()),
+        }
+    }
+
+    /// Whether a matched value can be this field; the checks `apply` makes,
+    /// without storing anything.
+    fn accepts(&self, value: &str) -> bool {
+        match &self.attr {
+            Attr::AscTime => value.bytes().any(|b| b.is_ascii_digit()),
+            Attr::Created | Attr::Msecs => value.parse::<f64>().is_ok(),
+            Attr::LevelName => {
+                Severity::from_python(value).is_some()
+                    || value.parse::<Severity>().is_ok()
+                    || is_custom_level(value)
+            }
+            Attr::LevelNo => value.parse::<i64>().is_ok(),
+            Attr::Name | Attr::Message => true,
+            Attr::Other(_) => self.kind == Kind::Text || number(value, self.kind).is_some(),
+        }
+    }
+
+    /// Store a matched value. Returns `None` if the value can't be this
+    /// field, so the line is not a record start.
+    fn apply(&self, entry: &mut LogEntry, value: &str) -> Option<()> {
+        match &self.attr {
+            Attr::AscTime => {
+                if !value.bytes().any(|b| b.is_ascii_digit()) {
+                    return None;
+                }
+                entry.timestamp = Some(value.to_string());
+            }
+            Attr::Created => {
+                let secs: f64 = value.parse().ok()?;
+                if entry.timestamp.is_none() {
+                    entry.timestamp = DateTime::from_timestamp_micros((secs * 1e6) as i64)
+                        .map(|ts| ts.to_rfc3339_opts(SecondsFormat::Micros, true));
+                }
+            }
+            // `%(asctime)s.%(msecs)03d` with a `datefmt` that drops millis
+            Attr::Msecs => match &mut entry.timestamp {
+                Some(ts) if !ts.contains(['.', ',']) => {
+                    let millis: f64 = value.parse().ok()?;
+                    ts.push_str(&format!(".{:03}", millis as u32));
+                }
+                _ => entry.set_field("msecs", number(value, self.kind)?),
+            },
+            Attr::LevelName => {
+                entry.level = Severity::from_python(value).or_else(|| value.parse().ok());
+                if entry.level.is_none() {
+                    if !is_custom_level(value) {
+                        return None;
+                    }
+                    entry.set_field("levelname", value);
+                }
+            }
+            Attr::LevelNo => {
+                let levelno: i64 = value.parse().ok()?;
+                entry.level = Some(level_from_number(levelno));
+            }
+            Attr::Name => entry.service = Some(value.to_string()),
+            Attr::Message => entry.message = Some(value.to_string()),
+            Attr::Other(name) => entry.set_field(name.as_str(), number(value, self.kind)?),
+        }
+        Some(())
+    }
+}
+
+/// A level name registered with `logging.addLevelName`: upper case, digits
+/// and underscores.
+fn is_custom_level(value: &str) -> bool {
+    !value.is_empty()
+        && value
+            .bytes()
+            .all(|b| b.is_ascii_uppercase() || b.is_ascii_digit() || b == b'_')
+}
+
+/// Length of the number at the start of `rest`, after any padding spaces.
+fn number_len(rest: &str, kind: Kind) -> Option<usize> {
+    let pad = rest.len() - rest.trim_start_matches(' ').len();
+    let digits = rest[pad..]
+        .bytes()
+        .enumerate()
+        .take_while(|&(i, b)| {
+            b.is_ascii_digit() || (i == 0 && b == b'-') || (kind == Kind::Float && b == b'.')
+        })
+        .count();
+    (digits > 0).then_some(pad + digits)
+}
+
+fn number(value: &str, kind: Kind) -> Option<FieldValue> {
+    Some(match kind {
+        Kind::Text => value.into(),
+        Kind::Int => FieldValue::Int(value.parse().ok()?),
+        Kind::Float => FieldValue::Float(value.parse().ok()?),
+    })
+}
+
+/// Numeric `logging` levels; values between the named ones round up.
+fn level_from_number(levelno: i64) -> Severity {
+    match levelno {
+        ..=0 => Severity::Trace,
+        1..=10 => Severity::Debug,
+        11..=20 => Severity::Info,
+        21..=30 => Severity::Warn,
+        31..=40 => Severity::Error,
+        _ => Severity::Critical,
+    }
+}
+
+/// A [`PythonFormat`] as a registrable parser. Lines that don't match the
+/// format (tracebacks, multi-line messages) continue the previous record.
+pub struct PythonFormatParser {
+    format: Arc<PythonFormat>,
+    multiline: Multiline,
+}
+
+impl PythonFormatParser {
+    /// Compile `format` (see [`PythonFormat::new`]).
+    pub fn new(format: &str) -> Result<Self> {
+        let format = Arc::new(PythonFormat::new(format)?);
+        let matcher = Arc::clone(&format);
+        let multiline = Multiline::new().start_with(move |line| {
+            std::str::from_utf8(line).is_ok_and(|line| matcher.is_match(line))
+        });
+        Ok(Self { format, multiline })
+    }
+}
+
+impl LogParser for PythonFormatParser {
+    fn name(&self) -> &str {
+        "python_format"
+    }
+
+    fn description(&self) -> &str {
+        "Python logging output in a custom --python-format layout"
+    }
+
+    fn parse_line(&self, line: &[u8]) -> Option<LogEntry> {
+        self.format.parse_line(std::str::from_utf8(line).ok()?)
+    }
+
+    fn multiline(&self) -> Option<&Multiline> {
+        Some(&self.multiline)
+    }
+
+    fn parse_record(&self, first: &[u8], continuation: &[&[u8]]) -> Option<LogEntry> {
+        let entry = self.parse_line(first)?;
+        Some(python_web::attach_continuation(entry, continuation))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "2025-08-31 22:50:01,234 [INFO] app.api:42 - started [pid 7]
+2025-08-31 22:50:02,001 [ERROR   ] app.db:117 - query failed
+Traceback (most recent call last):
+  File \"/app/db.py\", line 117, in run
+    cursor.execute(sql)
+TimeoutError: timed out
+not a record
+";
+
+    #[test]
+    fn compiles_and_matches_formats() {
+        let parser = PythonFormatParser::new(
+            "%(asctime)s [%(levelname)-8s] %(name)s:%(lineno)d - %(message)s",
+        )
+        .unwrap();
+        let v = parser.parse(SAMPLE).unwrap();
+        assert_eq!(v.len(), 2);
+        assert_eq!(v[0].timestamp.as_deref(), Some("2025-08-31 22:50:01,234"));
+        assert_eq!(v[0].level, Some(Severity::Info));
+        assert_eq!(v[0].service.as_deref(), Some("app.api"));
+        assert_eq!(v[0].message.as_deref(), Some("started [pid 7]"));
+        assert_eq!(v[0].fields.get("lineno"), Some(&FieldValue::Int(42)));
+        assert_eq!(v[1].level, Some(Severity::Error));
+        assert!(v[1].fields.contains_key("exception"));
+        assert_eq!(v[1].message.as_deref(), Some("query failed"));
+
+        let format = PythonFormat::new(
+            "%(asctime)s.%(msecs)03d %(process)d:%(thread)d %(levelno)s %(funcName)s() %(message)s",
+        )
+        .unwrap();
+        let entry = format
+            .parse_line("Aug 31 22:50:01.007 812:1401 30 handle() disk low")
+            .unwrap();
+        assert_eq!(entry.timestamp.as_deref(), Some("Aug 31 22:50:01.007"));
+        assert_eq!(entry.level, Some(Severity::Warn));
+        assert_eq!(entry.fields.get("process"), Some(&FieldValue::Int(812)));
+        assert_eq!(entry.fields.get("thread"), Some(&FieldValue::Int(1401)));
+        assert_eq!(
+            entry.fields.get("funcName"),
+            Some(&FieldValue::from("handle"))
+        );
+        assert_eq!(entry.message.as_deref(), Some("disk low"));
+
+        let basic = PythonFormat::new("%(levelname)s:%(name)s:%(message)s").unwrap();
+        assert!(
+            basic
+                .parse_line("Traceback (most recent call last):")
+                .is_none()
+        );
+        assert_eq!(
+            basic
+                .parse_line("SUCCESS:root:done")
+                .unwrap()
+                .fields
+                .get("levelname"),
+            Some(&FieldValue::from("SUCCESS"))
+        );
+
+        assert!(PythonFormat::new("%(name)s%(message)s").is_err());
+        assert!(PythonFormat::new("%(message)q").is_err());
+        assert!(PythonFormat::new("%(message").is_err());
+        assert!(PythonFormat::new("no fields").is_err());
+    }
+
+    #[test]
+    fn is_match_agrees_with_parse_line() {
+        let format =
+            PythonFormat::new("%(asctime)s [%(levelname)-8s] %(name)s:%(lineno)d - %(message)s")
+                .unwrap();
+        let extra = [
+            "2025-08-31 22:50:03,000 [lower] app:1 - custom level must be upper case",
+            "2025-08-31 22:50:03,000 [INFO] app:x - lineno must be a number",
+        ];
+        for line in SAMPLE.lines().chain(extra) {
+            assert_eq!(
+                format.is_match(line),
+                format.parse_line(line).is_some(),
+                "{line:?}"
+            );
+        }
+    }
+}
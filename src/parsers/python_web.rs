@@ -1,106 +1,96 @@
-use crate::config::LogEntry;
+use crate::config::{FieldValue, Fields, LogEntry};
+use crate::parsers::multiline::Multiline;
+use crate::parsers::{LogParser, parse_chunked};
+use crate::severity::Severity;
 use anyhow::Result;
-use memchr::memchr_iter;
-#[cfg(feature = "parallel")]
-use rayon::prelude::*;
-
-const CHUNK_BYTES: usize = 4 * 1024 * 1024; // 16 MB chunks
-const MIN_LINE_LEN: usize = 20;
+use memchr::memchr;
+use std::borrow::Cow;
+use std::sync::LazyLock;
 
 static SERVICE_PYTHON: &str = "python_web";
-static LEVEL_INFO: &str = "info";
-static LEVEL_WARN: &str = "warn";
-static LEVEL_ERROR: &str = "error";
+/// Records start at a `LEVEL YYYY-MM-DD` header; any other line (tracebacks,
+/// multi-line messages) continues the previous one.
+static MULTILINE: LazyLock<Multiline> = LazyLock::new(|| Multiline::new().start_with(is_header));
 
 pub fn parse_python_logs(input: &str) -> Result<Vec<LogEntry>> {
-    #[cfg(feature = "parallel")]
-    {
-        if input.len() > CHUNK_BYTES {
-            return Ok(parse_parallel(input));
-        }
+    Ok(parse_chunked(&PythonWebParser, input.as_bytes()))
+}
+
+/// Registry adapter for [`parse_python_logs`].
+pub struct PythonWebParser;
+
+impl LogParser for PythonWebParser {
+    fn name(&self) -> &str {
+        "python_web"
+    }
+
+    fn aliases(&self) -> &[&str] {
+        &["python"]
+    }
+
+    fn description(&self) -> &str {
+        "Python logging output (LEVEL date time module message)"
+    }
+
+    fn parse_line(&self, line: &[u8]) -> Option<LogEntry> {
+        parse_line(line)
+    }
+
+    fn parse_record(&self, first: &[u8], continuation: &[&[u8]]) -> Option<LogEntry> {
+        parse_record(first, continuation)
+    }
+
+    fn multiline(&self) -> Option<&Multiline> {
+        Some(&MULTILINE)
+    }
+
+    fn sniff(&self, line: &[u8]) -> bool {
+        is_header(line)
     }
-    Ok(parse_single(input.as_bytes()))
 }
 
-#[cfg(feature = "parallel")]
-fn parse_parallel(input: &str) -> Vec<LogEntry> {
-    let bytes = input.as_bytes();
-    let len = bytes.len();
-
-    // split into newline-aligned chunks
-    let mut ranges = Vec::new();
-    let mut start = 0;
-    while start < len {
-        let mut end = (start + CHUNK_BYTES).min(len);
-        if end < len {
-            while end < len && bytes[end] != b'\n' {
-                end += 1;
-            }
-            if end < len {
-                end += 1;
-            }
-        } else {
-            end = len;
-        }
-        ranges.push(start..end);
-        start = end;
-    }
-
-    let parts: Vec<Vec<LogEntry>> = ranges
-        .into_par_iter()
-        .map(|r| {
-            let approx_lines = (r.end - r.start) / 80;
-            let mut out = Vec::with_capacity(approx_lines);
-            parse_chunk(&bytes[r], &mut out);
-            out
-        })
-        .collect();
-
-    let total: usize = parts.iter().map(Vec::len).sum();
-    let mut result = Vec::with_capacity(total);
-    for mut p in parts {
-        result.append(&mut p);
-    }
-    result
+/// `LEVEL YYYY-MM-DD ...` with a standard `logging` level name. Any other
+/// line continues the previous record (tracebacks, multi-line messages).
+pub(crate) fn is_header(line: &[u8]) -> bool {
+    let Some(sp) = memchr(b' ', line) else {
+        return false;
+    };
+    let date = &line[sp + 1..];
+    is_level(&line[..sp])
+        && date.len() >= 10
+        && date[..4].iter().all(u8::is_ascii_digit)
+        && date[4] == b'-'
+        && date[7] == b'-'
 }
 
-fn parse_single(bytes: &[u8]) -> Vec<LogEntry> {
-    let mut out = Vec::with_capacity(bytes.len() / 80);
-    parse_chunk(bytes, &mut out);
-    out
+/// A standard `logging` level name, in any case (`WARNING`, `warning`,
+/// `Error`).
+pub(crate) fn is_level(word: &[u8]) -> bool {
+    const LEVELS: [&[u8]; 8] = [
+        b"NOTSET",
+        b"DEBUG",
+        b"INFO",
+        b"WARNING",
+        b"WARN",
+        b"ERROR",
+        b"CRITICAL",
+        b"FATAL",
+    ];
+    LEVELS.iter().any(|level| word.eq_ignore_ascii_case(level))
 }
 
-// fast chunk parser using memchr
-fn parse_chunk(bytes: &[u8], out: &mut Vec<LogEntry>) {
-    let mut start = 0;
-    let mut line_starts = Vec::with_capacity(64);
-    let mut line_ends = Vec::with_capacity(64);
-
-    // collect line boundaries in batch
-    for nl in memchr_iter(b'\n', bytes) {
-        line_starts.push(start);
-        line_ends.push(nl);
-        start = nl + 1;
-    }
-    if start < bytes.len() {
-        line_starts.push(start);
-        line_ends.push(bytes.len());
-    }
-
-    // parse each line
-    for (&s, &e) in line_starts.iter().zip(line_ends.iter()) {
-        let line = &bytes[s..e];
-        if line.len() >= MIN_LINE_LEN {
-            if let Some(entry) = parse_line(line) {
-                out.push(entry);
-            }
-        }
-    }
+// header line plus continuation lines, whichever rules grouped them
+fn parse_record(first: &[u8], continuation: &[&[u8]]) -> Option<LogEntry> {
+    let entry = parse_line(first)?;
+    Some(attach_continuation(entry, continuation))
 }
 
 // parse single line without allocations
-fn parse_line(line: &[u8]) -> Option<LogEntry> {
-    let s = unsafe { std::str::from_utf8_unchecked(line) };
+pub(crate) fn parse_line(line: &[u8]) -> Option<LogEntry> {
+    if !is_header(line) {
+        return None;
+    }
+    let s = std::str::from_utf8(line).ok()?;
     let len = s.len();
     let bytes = s.as_bytes();
 
@@ -111,23 +101,21 @@ fn parse_line(line: &[u8]) -> Option<LogEntry> {
         i += 1;
     }
     let level_str = &s[0..i];
-    i += 1;
+    i = (i + 1).min(len);
 
-    // Timestamp (next token)
+    // Timestamp: date token, plus the time-of-day token ("hh:mm:ss,mmm") if present
     let ts_start = i;
     while i < len && bytes[i] != b' ' {
         i += 1;
     }
-    let timestamp = &s[ts_start..i];
-    i += 1;
-
-    // Skip microseconds if present
-    if i < len && bytes[i] == b',' {
+    if i + 1 < len && bytes[i + 1].is_ascii_digit() {
+        i += 1;
         while i < len && bytes[i] != b' ' {
             i += 1;
         }
-        i += 1;
     }
+    let timestamp = &s[ts_start..i];
+    i = (i + 1).min(len);
 
     // Module name
     let mod_start = i;
@@ -135,41 +123,297 @@ fn parse_line(line: &[u8]) -> Option<LogEntry> {
         i += 1;
     }
     let module = &s[mod_start..i];
-    i += 1;
+    i = (i + 1).min(len);
 
     // Message
     let message = if i < len { &s[i..] } else { "" };
 
-    let level_static = match level_str.to_ascii_lowercase().as_str() {
-        "error" => LEVEL_ERROR,
-        "warn" | "warning" => LEVEL_WARN,
-        _ => LEVEL_INFO,
-    };
+    // `parse` covers the other names in any case, leaving only `notset`
+    let level = Severity::from_python(level_str)
+        .or_else(|| level_str.parse().ok())
+        .unwrap_or(Severity::Trace);
 
     Some(LogEntry {
         timestamp: Some(timestamp.to_string()),
         host: Some(module.to_string()),
         service: Some(SERVICE_PYTHON.to_string()),
-        level: Some(level_static.to_string()),
+        level: Some(level),
         message: Some(message.to_string()),
+        ..Default::default()
     })
 }
 
+/// Fold continuation lines into `entry`. Text before a `Traceback` line
+/// extends the message; the traceback itself is kept verbatim in the
+/// `traceback` field and parsed into the `exception` field.
+pub(crate) fn attach_continuation(mut entry: LogEntry, lines: &[&[u8]]) -> LogEntry {
+    if lines.is_empty() {
+        return entry;
+    }
+    let text: Vec<Cow<str>> = lines.iter().map(|l| String::from_utf8_lossy(l)).collect();
+    let lines: Vec<&str> = text.iter().map(AsRef::as_ref).collect();
+    let tb_start = lines
+        .iter()
+        .position(|l| l.starts_with(TRACEBACK_HEADER))
+        .unwrap_or(lines.len());
+
+    if tb_start > 0 {
+        let message = entry.message.get_or_insert_with(String::new);
+        for line in &lines[..tb_start] {
+            message.push('\n');
+            message.push_str(line);
+        }
+    }
+    if tb_start < lines.len() {
+        let traceback = &lines[tb_start..];
+        if let Some(exception) = parse_traceback(traceback) {
+            entry.set_field("exception", exception);
+        }
+        entry.set_field("traceback", traceback.join("\n"));
+    }
+    entry
+}
+
+const TRACEBACK_HEADER: &str = "Traceback (most recent call last):";
+const CONTEXT_SEPARATOR: &str =
+    "During handling of the above exception, another exception occurred:";
+const CAUSE_SEPARATOR: &str =
+    "The above exception was the direct cause of the following exception:";
+
+#[derive(Default)]
+struct Exception {
+    kind: Option<String>,
+    message: Option<String>,
+    frames: Vec<FieldValue>,
+    /// How the previous exception in the traceback relates to this one.
+    link: Option<&'static str>,
+}
+
+/// Parse a (possibly chained) traceback into
+/// `{type, message, frames: [{file, line, function, code}], cause|context}`,
+/// where `cause`/`context` hold the earlier exception like Python's
+/// `__cause__` / `__context__`.
+fn parse_traceback(lines: &[&str]) -> Option<Fields> {
+    let mut chain: Vec<Exception> = Vec::new();
+    let mut link = None;
+
+    for line in lines {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        if line.starts_with(TRACEBACK_HEADER) {
+            chain.push(Exception {
+                link: link.take(),
+                ..Default::default()
+            });
+            continue;
+        }
+        if trimmed == CONTEXT_SEPARATOR {
+            link = Some("context");
+            continue;
+        }
+        if trimmed == CAUSE_SEPARATOR {
+            link = Some("cause");
+            continue;
+        }
+        let Some(current) = chain.last_mut() else {
+            continue;
+        };
+
+        if let Some(rest) = trimmed.strip_prefix("File \"") {
+            current.frames.push(parse_frame(rest).into());
+        } else if line.starts_with(' ') {
+            // source line of the last frame; skip 3.11+ `^^^^` markers
+            let is_marker = trimmed.bytes().all(|b| matches!(b, b'^' | b'~' | b' '));
+            if let Some(FieldValue::Map(frame)) = current.frames.last_mut()
+                && !is_marker
+                && !frame.contains_key("code")
+            {
+                frame.insert("code".into(), trimmed.into());
+            }
+        } else if current.kind.is_none() {
+            let (kind, message) = match line.split_once(": ") {
+                Some((kind, message)) => (kind, Some(message)),
+                None => (line.trim_end_matches(':'), None),
+            };
+            current.kind = Some(kind.to_string());
+            current.message = message.map(str::to_string);
+        } else {
+            // multi-line exception message
+            let message = current.message.get_or_insert_with(String::new);
+            message.push('\n');
+            message.push_str(line);
+        }
+    }
+
+    let mut exception: Option<Fields> = None;
+    for step in chain {
+        let mut fields = Fields::new();
+        if let Some(kind) = step.kind {
+            fields.insert("type".into(), kind.into());
+        }
+        if let Some(message) = step.message {
+            fields.insert("message".into(), message.into());
+        }
+        fields.insert("frames".into(), step.frames.into());
+        if let (Some(link), Some(previous)) = (step.link, exception.take()) {
+            fields.insert(link.into(), previous.into());
+        }
+        exception = Some(fields);
+    }
+    exception
+}
+
+/// `/app/views.py", line 42, in index` (after the leading `File "`).
+fn parse_frame(rest: &str) -> Fields {
+    let mut frame = Fields::new();
+    let (file, rest) = rest.split_once('"').unwrap_or((rest, ""));
+    frame.insert("file".into(), file.into());
+    for part in rest.split(", ") {
+        if let Some(line) = part.strip_prefix("line ")
+            && let Ok(line) = line.parse::<i64>()
+        {
+            frame.insert("line".into(), line.into());
+        } else if let Some(function) = part.strip_prefix("in ") {
+            frame.insert("function".into(), function.into());
+        }
+    }
+    frame
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::parsers::MultilineParser;
+    use std::sync::Arc;
 
     const SAMPLE: &str = r#"INFO 2025-08-31 22:50:01,234 views.index Some log message
 WARNING 2025-08-31 22:51:02,567 views.auth Something might be wrong
 ERROR 2025-08-31 22:52:03,890 views.api Exception occurred
+DEBUG 2025-08-31 22:52:04,001 views.api Cache miss for key user:42
+CRITICAL 2025-08-31 22:52:05,123 views.api Database unreachable
 "#;
 
     #[test]
     fn parse_sample() {
         let v = parse_python_logs(SAMPLE).unwrap();
-        assert_eq!(v.len(), 3);
+        assert_eq!(v.len(), 5);
         assert_eq!(v[0].host.as_deref(), Some("views.index"));
-        assert_eq!(v[1].level.as_deref(), Some("warn"));
-        assert_eq!(v[2].level.as_deref(), Some("error"));
+        assert_eq!(v[0].timestamp.as_deref(), Some("2025-08-31 22:50:01,234"));
+        assert_eq!(v[1].level, Some(Severity::Warn));
+        assert_eq!(v[2].level, Some(Severity::Error));
+        assert_eq!(v[3].level, Some(Severity::Debug));
+        assert_eq!(v[4].level, Some(Severity::Critical));
+    }
+
+    const TRACEBACK: &str = r#"ERROR 2025-08-31 22:52:03,890 views.api Exception occurred
+while handling /api/users
+Traceback (most recent call last):
+  File "/app/views.py", line 42, in index
+    return users[key]
+           ~~~~~^^^^^
+KeyError: 'id'
+
+During handling of the above exception, another exception occurred:
+
+Traceback (most recent call last):
+  File "/app/views.py", line 44, in index
+    raise ValueError("bad\nrequest")
+ValueError: bad
+request
+INFO 2025-08-31 22:52:04,001 views.api Recovered
+"#;
+
+    #[test]
+    fn folds_tracebacks() {
+        let v = parse_python_logs(TRACEBACK).unwrap();
+        assert_eq!(v.len(), 2);
+        assert_eq!(
+            v[0].message.as_deref(),
+            Some("Exception occurred\nwhile handling /api/users")
+        );
+        let Some(FieldValue::Map(exc)) = v[0].fields.get("exception") else {
+            panic!("missing exception");
+        };
+        assert_eq!(exc.get("type"), Some(&FieldValue::from("ValueError")));
+        assert_eq!(exc.get("message"), Some(&FieldValue::from("bad\nrequest")));
+        let Some(FieldValue::List(frames)) = exc.get("frames") else {
+            panic!("missing frames");
+        };
+        let FieldValue::Map(frame) = &frames[0] else {
+            panic!("frame is not a map");
+        };
+        assert_eq!(frame.get("file"), Some(&FieldValue::from("/app/views.py")));
+        assert_eq!(frame.get("line"), Some(&FieldValue::Int(44)));
+        assert_eq!(frame.get("function"), Some(&FieldValue::from("index")));
+        let Some(FieldValue::Map(context)) = exc.get("context") else {
+            panic!("missing chained exception");
+        };
+        assert_eq!(context.get("type"), Some(&FieldValue::from("KeyError")));
+        assert_eq!(context.get("message"), Some(&FieldValue::from("'id'")));
+        assert_eq!(v[1].message.as_deref(), Some("Recovered"));
+
+        // grouping by --stack-traces on top of the header rule
+        let next = TRACEBACK.find("INFO").unwrap();
+        let input = format!(
+            "ERROR 2025-08-31 22:52:03,890 views.api Exception occurred\n{}",
+            &TRACEBACK[TRACEBACK.find("Traceback").unwrap()..]
+        );
+        let parser = MultilineParser::new(Arc::new(PythonWebParser), Multiline::stack_traces());
+        let v = parser.parse(&input).unwrap();
+        assert_eq!(v.len(), 2);
+        assert_eq!(v[0].message.as_deref(), Some("Exception occurred"));
+        let Some(FieldValue::Map(exc)) = v[0].fields.get("exception") else {
+            panic!("missing exception with --stack-traces");
+        };
+        assert_eq!(exc.get("type"), Some(&FieldValue::from("ValueError")));
+        assert_eq!(exc.get("message"), Some(&FieldValue::from("bad\nrequest")));
+        let Some(FieldValue::Map(context)) = exc.get("context") else {
+            panic!("missing chained exception with --stack-traces");
+        };
+        assert_eq!(context.get("type"), Some(&FieldValue::from("KeyError")));
+        let traceback = &TRACEBACK[TRACEBACK.find("Traceback").unwrap()..next];
+        assert_eq!(
+            v[0].fields.get("traceback"),
+            Some(&FieldValue::from(traceback.trim_end()))
+        );
+        assert_eq!(v[1].message.as_deref(), Some("Recovered"));
+
+        // a split inside the traceback moves to the next entry
+        assert_eq!(MULTILINE.record_start(TRACEBACK.as_bytes(), 100), next);
+        assert_eq!(MULTILINE.record_start(TRACEBACK.as_bytes(), next), next);
+    }
+
+    #[test]
+    fn level_names_match_in_any_case() {
+        let v = parse_python_logs(concat!(
+            "warning 2025-08-31 22:52:03,890 views.api slow\n",
+            "Error 2025-08-31 22:52:04,001 views.api boom\n",
+            "notset 2025-08-31 22:52:05,002 views.api noise\n",
+        ))
+        .unwrap();
+        assert_eq!(v.len(), 3);
+        assert_eq!(v[0].level, Some(Severity::Warn));
+        assert_eq!(v[1].level, Some(Severity::Error));
+        assert_eq!(v[2].level, Some(Severity::Trace));
+        assert!(parse_line(b"Verbose 2025-08-31 22:52:03,890 views.api x").is_none());
+    }
+
+    #[test]
+    fn parse_line_handles_truncated_lines() {
+        assert!(parse_line(b"INFO").is_none());
+        assert!(parse_line(b"INFO x").is_none());
+        let entry = parse_line(b"INFO 2025-08-31").unwrap();
+        assert_eq!(entry.timestamp.as_deref(), Some("2025-08-31"));
+        assert_eq!(entry.host.as_deref(), Some(""));
+        assert_eq!(entry.message.as_deref(), Some(""));
+
+        // a short header still starts its own record
+        let v =
+            parse_python_logs("ERROR 2025-08-31 22:52:03,890 views.api boom\nINFO 2025-08-31\n")
+                .unwrap();
+        assert_eq!(v.len(), 2);
+        assert_eq!(v[0].message.as_deref(), Some("boom"));
     }
 }
@@ -0,0 +1,124 @@
+//! Lines described by regexes with named groups
+//! (`(?P<timestamp>\S+) (?P<level>\w+) (?P<message>.*)`).
+//!
+//! Groups named `timestamp`, `level`, `message`, `host` or `service` fill
+//! the entry; any other named group is kept in `fields` as text. With
+//! several alternatives, a [`RegexSet`] finds the ones that match in one
+//! pass and the first of them (in the order given) captures.
+
+use crate::config::LogEntry;
+use crate::parsers::LogParser;
+use crate::parsers::grok::set_capture;
+use anyhow::{Context, Result, bail};
+use regex::{Regex, RegexSet};
+
+/// One or more regexes, tried in order, as the `regex` preset.
+pub struct RegexParser {
+    set: RegexSet,
+    regexes: Vec<Regex>,
+}
+
+impl RegexParser {
+    pub fn new<S: AsRef<str>>(patterns: &[S]) -> Result<Self> {
+        if patterns.is_empty() {
+            bail!("no regex given");
+        }
+        let regexes = patterns
+            .iter()
+            .map(|p| {
+                let regex = Regex::new(p.as_ref())?;
+                if regex.capture_names().flatten().next().is_none() {
+                    bail!("'{}' has no named groups", p.as_ref());
+                }
+                Ok(regex)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let set =
+            RegexSet::new(patterns.iter().map(AsRef::as_ref)).context("building the regex set")?;
+        Ok(Self { set, regexes })
+    }
+
+    fn parse_str(&self, line: &str) -> Option<LogEntry> {
+        let regex = match self.regexes.as_slice() {
+            [only] => only,
+            all => &all[self.set.matches(line).iter().next()?],
+        };
+        let caps = regex.captures(line)?;
+
+        let mut entry = LogEntry::default();
+        for name in regex.capture_names().flatten() {
+            if let Some(value) = caps.name(name)
+                && !set_capture(&mut entry, name, value.as_str())
+            {
+                entry.set_field(name, value.as_str());
+            }
+        }
+        if entry.message.is_none() {
+            entry.message = Some(line.trim_end().to_string());
+        }
+        Some(entry)
+    }
+}
+
+impl LogParser for RegexParser {
+    fn name(&self) -> &str {
+        "regex"
+    }
+
+    fn description(&self) -> &str {
+        "Lines matching the --regex named-group patterns"
+    }
+
+    fn parse_line(&self, line: &[u8]) -> Option<LogEntry> {
+        self.parse_str(std::str::from_utf8(line).ok()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::FieldValue;
+    use crate::severity::Severity;
+
+    #[test]
+    fn named_groups_fill_entry() {
+        let parser = RegexParser::new(&[
+            r"^(?P<timestamp>\S+) (?P<level>[A-Z]+) \[(?P<service>[^\]]+)\] (?P<message>.*)$",
+            r"^(?P<timestamp>\S+) (?P<level>\w+) (?P<message>.*)$",
+            r"^(?P<timestamp>\S+) (?P<rest>.*)$",
+        ])
+        .unwrap();
+        let input = "2025-08-31T22:50:01Z ERROR [db] connection reset\n\
+                     2025-08-31T22:50:02Z warn disk almost full\n\
+                     2025-08-31T22:50:03Z (no level) 42\n\
+                     nospace\n";
+        let v = parser.parse(input).unwrap();
+        assert_eq!(v.len(), 3);
+
+        assert_eq!(v[0].timestamp.as_deref(), Some("2025-08-31T22:50:01Z"));
+        assert_eq!(v[0].level, Some(Severity::Error));
+        assert_eq!(v[0].service.as_deref(), Some("db"));
+        assert_eq!(v[0].message.as_deref(), Some("connection reset"));
+
+        assert_eq!(v[1].level, Some(Severity::Warn));
+        assert_eq!(v[1].service, None);
+        assert_eq!(v[1].message.as_deref(), Some("disk almost full"));
+
+        assert_eq!(v[2].level, None);
+        assert_eq!(
+            v[2].fields.get("rest"),
+            Some(&FieldValue::from("(no level) 42"))
+        );
+        assert_eq!(
+            v[2].message.as_deref(),
+            Some("2025-08-31T22:50:03Z (no level) 42")
+        );
+    }
+
+    #[test]
+    fn rejects_regexes_without_named_groups() {
+        assert!(RegexParser::new::<&str>(&[]).is_err());
+        assert!(RegexParser::new(&[r"\d+"]).is_err());
+        assert!(RegexParser::new(&["(?P<message>"]).is_err());
+    }
+}
@@ -0,0 +1,206 @@
+use std::sync::{Arc, LazyLock, RwLock};
+
+use super::{
+    LogParser, apache, container, django, flask, generic, journalctl, journald, json, logfmt,
+    nginx, python_web, rfc5424, syslog,
+};
+
+/// Ordered collection of parsers, resolvable by name or alias.
+///
+/// Registration order matters: it is the order `--list-presets` prints and
+/// the tie-break order for anything that walks every parser.
+pub struct ParserRegistry {
+    parsers: Vec<Arc<dyn LogParser>>,
+}
+
+impl ParserRegistry {
+    /// An empty registry.
+    pub fn new() -> Self {
+        Self {
+            parsers: Vec::new(),
+        }
+    }
+
+    /// A registry pre-populated with the built-in parsers.
+    pub fn with_builtins() -> Self {
+        let mut registry = Self::new();
+        registry.register(syslog::SyslogParser);
+        registry.register(rfc5424::Rfc5424Parser);
+        registry.register(nginx::NginxParser);
+        registry.register(apache::ApacheParser);
+        registry.register(journalctl::JournalParser);
+        registry.register(journald::JournalJsonParser);
+        registry.register(journald::JournalExportParser);
+        registry.register(python_web::PythonWebParser);
+        registry.register(django::DjangoParser);
+        registry.register(flask::FlaskParser);
+        registry.register(container::ContainerParser::docker());
+        registry.register(container::ContainerParser::cri());
+        registry.register(json::JsonParser::default());
+        registry.register(logfmt::LogfmtParser);
+        registry.register(generic::GenericParser);
+        registry
+    }
+
+    /// Add a parser. A parser with the same name replaces the existing one
+    /// in place; otherwise it is appended.
+    pub fn register<P: LogParser + 'static>(&mut self, parser: P) {
+        let parser: Arc<dyn LogParser> = Arc::new(parser);
+        match self.parsers.iter_mut().find(|p| p.name() == parser.name()) {
+            Some(slot) => *slot = parser,
+            None => self.parsers.push(parser),
+        }
+    }
+
+    /// Resolve a preset name or alias.
+    pub fn get(&self, name: &str) -> Option<Arc<dyn LogParser>> {
+        self.parsers
+            .iter()
+            .find(|p| p.name() == name)
+            .or_else(|| self.parsers.iter().find(|p| p.aliases().contains(&name)))
+            .cloned()
+    }
+
+    /// All parsers in registration order.
+    pub fn iter(&self) -> impl Iterator<Item = &Arc<dyn LogParser>> {
+        self.parsers.iter()
+    }
+}
+
+impl Default for ParserRegistry {
+    fn default() -> Self {
+        Self::with_builtins()
+    }
+}
+
+static GLOBAL: LazyLock<RwLock<ParserRegistry>> =
+    LazyLock::new(|| RwLock::new(ParserRegistry::with_builtins()));
+
+/// Register a parser in the process-wide registry used by [`super::parse`]
+/// and the CLI.
+pub fn register<P: LogParser + 'static>(parser: P) {
+    GLOBAL.write().unwrap().register(parser);
+}
+
+/// Resolve a preset name or alias in the process-wide registry.
+pub fn lookup(name: &str) -> Option<Arc<dyn LogParser>> {
+    GLOBAL.read().unwrap().get(name)
+}
+
+/// Snapshot of every parser in the process-wide registry.
+pub fn registered() -> Vec<Arc<dyn LogParser>> {
+    GLOBAL.read().unwrap().iter().cloned().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::LogEntry;
+
+    struct Upper;
+
+    impl LogParser for Upper {
+        fn name(&self) -> &str {
+            "upper"
+        }
+
+        fn aliases(&self) -> &[&str] {
+            &["shout"]
+        }
+
+        fn parse_line(&self, line: &[u8]) -> Option<LogEntry> {
+            let s = std::str::from_utf8(line).ok()?;
+            Some(LogEntry {
+                message: Some(s.to_uppercase()),
+                ..Default::default()
+            })
+        }
+    }
+
+    #[test]
+    fn resolves_builtins_and_custom_parsers() {
+        let mut registry = ParserRegistry::with_builtins();
+        assert!(registry.get("nginx").is_some());
+        assert_eq!(registry.get("python").unwrap().name(), "python_web");
+        assert!(registry.get("upper").is_none());
+
+        registry.register(Upper);
+        let v = registry.get("shout").unwrap().parse("a\nb\n").unwrap();
+        assert_eq!(v.len(), 2);
+        assert_eq!(v[1].message.as_deref(), Some("B"));
+        assert_eq!(registry.iter().last().unwrap().name(), "upper");
+    }
+
+    #[test]
+    fn builtins_reject_invalid_utf8() {
+        let registry = ParserRegistry::with_builtins();
+        let cases: [(&str, &[u8]); 5] = [
+            (
+                "nginx",
+                b"127.0.0.1 - - [10/Oct/2000:13:55:36 -0700] \"GET /\xff HTTP/1.1\" 200 5",
+            ),
+            (
+                "apache",
+                b"127.0.0.1 - - [10/Oct/2000:13:55:36 -0700] \"GET /\xff HTTP/1.1\" 200 5",
+            ),
+            ("syslog", b"Aug 31 22:50:01 web\xff sshd[1]: hi"),
+            ("journalctl", b"Aug 31 22:50:01 web\xff sshd[1]: hi"),
+            (
+                "python_web",
+                b"INFO 2025-08-31 22:50:01,234 views.index caf\xff",
+            ),
+        ];
+        for (name, line) in cases {
+            let parser = registry.get(name).unwrap();
+            let valid: Vec<u8> = line
+                .iter()
+                .map(|&b| if b == 0xff { b'x' } else { b })
+                .collect();
+            assert!(parser.parse_line(&valid).is_some(), "{}", name);
+            assert!(parser.parse_line(line).is_none(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn every_preset_survives_invalid_utf8() {
+        let registry = ParserRegistry::with_builtins();
+        for parser in registry.iter() {
+            for input in [&b"\xff"[..], b"\xff\n\xfe \xff\n", b"INFO \xff\n\n\xff"] {
+                assert!(parser.parse_bytes(input).is_ok(), "{}", parser.name());
+                assert!(!parser.sniff(input), "{}", parser.name());
+            }
+        }
+    }
+
+    #[test]
+    fn every_preset_survives_truncated_lines() {
+        let registry = ParserRegistry::with_builtins();
+        let lines: [&[u8]; 12] = [
+            b"127.0.0.1 - - [10/Oct/2000:13:55:36 -0700] \"GET / HTTP/1.1\" 200 5 \"-\" \"curl\" 0.001",
+            b"2025/05/12 06:25:24 [error] 12#12: *3 open() failed, client: 10.0.0.1",
+            b"[Mon May 12 06:25:24.123456 2025] [core:error] [pid 7] [client 10.0.0.1:5] boom",
+            b"<34>1 2025-05-12T06:25:24Z web01 sshd 7 ID47 [a b=\"c\"] hi",
+            b"<13>Aug 31 22:50:01 web01 sshd[1]: hi",
+            b"Aug 31 22:50:01 web01 sshd[1]: hi",
+            b"INFO 2025-08-31 22:50:01,234 views.index hello",
+            b"[31/Aug/2025 22:50:01] \"GET /api HTTP/1.1\" 200 5",
+            b"[2025-08-31 22:50:03,120] ERROR in app: Exception on /x [GET]",
+            b"{\"log\":\"INFO 2025-08-31 22:50:01,234 a b\\n\",\"stream\":\"stdout\",\"time\":\"2025-05-12T06:25:24Z\"}",
+            b"2025-05-12T06:25:24.123456789Z stdout F ts=1 level=info msg=\"a b\"",
+            b"__REALTIME_TIMESTAMP=1760220855003000\nMESSAGE=hi\n",
+        ];
+        for parser in registry.iter() {
+            for line in lines {
+                for end in 0..=line.len() {
+                    let prefix = &line[..end];
+                    parser.parse_line(prefix);
+                    parser.sniff(prefix);
+                    assert!(parser.parse_bytes(prefix).is_ok(), "{}", parser.name());
+                    for pos in 0..=end {
+                        assert!(parser.record_start(prefix, pos) <= end, "{}", parser.name());
+                    }
+                }
+            }
+        }
+    }
+}
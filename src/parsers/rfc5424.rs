@@ -0,0 +1,261 @@
+//! RFC 5424 syslog: `<PRI>VERSION TIMESTAMP HOSTNAME APP-NAME PROCID MSGID
+//! STRUCTURED-DATA [MSG]`, as written by rsyslog/syslog-ng.
+
+use crate::config::{Fields, LogEntry};
+use crate::parsers::LogParser;
+use crate::parsers::syslog::{facility_name, parse_pri};
+use crate::severity::Severity;
+use chrono::DateTime;
+
+const NIL: &str = "-";
+
+pub struct Rfc5424Parser;
+
+impl LogParser for Rfc5424Parser {
+    fn name(&self) -> &str {
+        "rfc5424"
+    }
+
+    fn aliases(&self) -> &[&str] {
+        &["syslog5424"]
+    }
+
+    fn description(&self) -> &str {
+        "RFC 5424 syslog with structured data"
+    }
+
+    fn parse_line(&self, line: &[u8]) -> Option<LogEntry> {
+        parse_line(line)
+    }
+}
+
+fn parse_line(line: &[u8]) -> Option<LogEntry> {
+    let s = std::str::from_utf8(line).ok()?.trim_end_matches('\r');
+
+    // <PRI>VERSION
+    let rest = s.strip_prefix('<')?;
+    let close = rest.find('>')?;
+    let pri = parse_pri(&rest.as_bytes()[..close])?;
+    let (version, rest) = rest[close + 1..].split_once(' ')?;
+    if version.is_empty() || !version.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    let mut header = rest.splitn(6, ' ');
+    let timestamp = header.next()?;
+    let hostname = header.next()?;
+    let app_name = header.next()?;
+    let procid = header.next()?;
+    let msgid = header.next()?;
+    let remainder = header.next().unwrap_or(NIL);
+    if timestamp != NIL && DateTime::parse_from_rfc3339(timestamp).is_err() {
+        return None;
+    }
+
+    // STRUCTURED-DATA is required: NIL or at least one `[element]`
+    let (structured_data, msg) = match remainder.strip_prefix(NIL) {
+        Some(after) if after.is_empty() || after.starts_with(' ') => (Fields::new(), after),
+        _ if remainder.starts_with('[') => parse_structured_data(remainder)?,
+        _ => return None,
+    };
+    // MSG is separated from STRUCTURED-DATA by a space
+    let msg = match msg.strip_prefix(' ') {
+        Some(msg) => msg,
+        None if msg.is_empty() => msg,
+        None => return None,
+    };
+    let msg = msg.strip_prefix('\u{feff}').unwrap_or(msg);
+
+    let mut entry = LogEntry {
+        timestamp: non_nil(timestamp),
+        host: non_nil(hostname),
+        service: non_nil(app_name),
+        level: Severity::from_syslog(pri & 0x07),
+        message: Some(msg.to_string()),
+        ..Default::default()
+    };
+    if let Some(facility) = facility_name(pri) {
+        entry.set_field("facility", facility);
+    }
+    entry.set_field("version", version.parse::<i64>().ok()?);
+    if procid != NIL {
+        entry.set_field("procid", procid);
+    }
+    if msgid != NIL {
+        entry.set_field("msgid", msgid);
+    }
+    if !structured_data.is_empty() {
+        entry.set_field("structured_data", structured_data);
+    }
+    Some(entry)
+}
+
+fn non_nil(value: &str) -> Option<String> {
+    (value != NIL).then(|| value.to_string())
+}
+
+/// Parse `[id k="v" ...][id2 ...]`, returning `{id: {k: v}}` and the text
+/// after the last element. Param values may escape `"`, `\` and `]`.
+fn parse_structured_data(s: &str) -> Option<(Fields, &str)> {
+    let mut elements = Fields::new();
+    let mut rest = s;
+
+    while let Some(body) = rest.strip_prefix('[') {
+        let id_end = body.find([' ', ']'])?;
+        let mut params = Fields::new();
+        let mut cur = &body[id_end..];
+        loop {
+            cur = cur.trim_start_matches(' ');
+            if let Some(after) = cur.strip_prefix(']') {
+                rest = after;
+                break;
+            }
+            let eq = cur.find([' ', ']', '='])?;
+            let name = &cur[..eq];
+            if name.is_empty() {
+                return None;
+            }
+            let quoted = cur[eq..].strip_prefix("=\"")?;
+
+            let mut value = String::new();
+            let mut end = None;
+            let mut chars = quoted.char_indices();
+            while let Some((i, c)) = chars.next() {
+                match c {
+                    '\\' => {
+                        if let Some((_, next)) = chars.next() {
+                            if !matches!(next, '"' | '\\' | ']') {
+                                value.push('\\');
+                            }
+                            value.push(next);
+                        }
+                    }
+                    '"' => {
+                        end = Some(i);
+                        break;
+                    }
+                    _ => value.push(c),
+                }
+            }
+            params.insert(name.to_string(), value.into());
+            cur = &quoted[end? + 1..];
+        }
+        elements.insert(body[..id_end].to_string(), params.into());
+    }
+
+    Some((elements, rest))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::FieldValue;
+
+    #[test]
+    fn parses_header_fields() {
+        let entry = parse_line(
+            b"<34>1 2025-10-11T22:14:15.003Z mymachine.example.com su - ID47 - 'su root' failed",
+        )
+        .unwrap();
+        assert_eq!(entry.timestamp.as_deref(), Some("2025-10-11T22:14:15.003Z"));
+        assert_eq!(entry.host.as_deref(), Some("mymachine.example.com"));
+        assert_eq!(entry.service.as_deref(), Some("su"));
+        assert_eq!(entry.level, Some(Severity::Critical));
+        assert_eq!(entry.message.as_deref(), Some("'su root' failed"));
+        assert_eq!(
+            entry.fields.get("facility"),
+            Some(&FieldValue::from("auth"))
+        );
+        assert_eq!(entry.fields.get("version"), Some(&FieldValue::Int(1)));
+        assert_eq!(entry.fields.get("msgid"), Some(&FieldValue::from("ID47")));
+        assert!(entry.fields.get("procid").is_none());
+    }
+
+    #[test]
+    fn parses_structured_data_elements() {
+        let entry = parse_line(
+            br#"<165>1 2025-10-11T22:14:15.003Z host app 1234 ID47 [exampleSDID@32473 iut="3" eventID="1011"][examplePriority@32473 class="high \"x\""] An application event"#,
+        )
+        .unwrap();
+        assert_eq!(entry.level, Some(Severity::Notice));
+        assert_eq!(entry.message.as_deref(), Some("An application event"));
+        assert_eq!(entry.fields.get("procid"), Some(&FieldValue::from("1234")));
+        let Some(FieldValue::Map(sd)) = entry.fields.get("structured_data") else {
+            panic!("missing structured data");
+        };
+        let Some(FieldValue::Map(example)) = sd.get("exampleSDID@32473") else {
+            panic!("missing SD element");
+        };
+        assert_eq!(example.get("eventID"), Some(&FieldValue::from("1011")));
+        let Some(FieldValue::Map(priority)) = sd.get("examplePriority@32473") else {
+            panic!("missing SD element");
+        };
+        assert_eq!(
+            priority.get("class"),
+            Some(&FieldValue::from(r#"high "x""#))
+        );
+    }
+
+    #[test]
+    fn nil_values_are_left_unset() {
+        let entry = parse_line(b"<13>1 - - - - - -").unwrap();
+        assert_eq!(entry.timestamp, None);
+        assert_eq!(entry.host, None);
+        assert_eq!(entry.service, None);
+        assert_eq!(entry.message.as_deref(), Some(""));
+        assert!(entry.fields.get("structured_data").is_none());
+    }
+
+    #[test]
+    fn rejects_bsd_syslog() {
+        assert!(parse_line(b"<34>Oct 11 22:14:15 mymachine su: 'su root' failed").is_none());
+    }
+
+    #[test]
+    fn rejects_truncated_lines() {
+        assert!(parse_line(b"<34>1 2025-10-11T22:14:15.003Z host su").is_none());
+        assert!(parse_line(br#"<34>1 - host su - - [id k="v"#).is_none());
+        assert!(parse_line(br#"<34>1 - host su - - [id k=v] msg"#).is_none());
+    }
+
+    #[test]
+    fn requires_structured_data() {
+        assert!(parse_line(b"<34>1 - host su - - -foo").is_none());
+        assert!(parse_line(b"<34>1 - host su - - no structured data").is_none());
+        let entry = parse_line(b"<34>1 - host su - - - -foo").unwrap();
+        assert_eq!(entry.message.as_deref(), Some("-foo"));
+    }
+
+    #[test]
+    fn requires_an_rfc3339_timestamp() {
+        assert!(parse_line(b"<34>1 Oct-11 host su - - - msg").is_none());
+        assert!(parse_line(b"<34>1 2025-10-11 host su - - - msg").is_none());
+        assert!(parse_line(b"<34>1 2025-10-11T22:14:15+02:00 host su - - - msg").is_some());
+    }
+
+    #[test]
+    fn requires_a_space_after_structured_data() {
+        assert!(parse_line(br#"<34>1 - host su - - [id k="v"]msg"#).is_none());
+        let entry = parse_line(br#"<34>1 - host su - - [id k="v"]"#).unwrap();
+        assert_eq!(entry.message.as_deref(), Some(""));
+    }
+
+    #[test]
+    fn param_names_stop_at_the_element() {
+        assert!(parse_line(br#"<34>1 - host su - - [id k] x="y"] msg"#).is_none());
+        assert!(parse_line(br#"<34>1 - host su - - [id ="y"] msg"#).is_none());
+    }
+
+    #[test]
+    fn rejects_invalid_utf8() {
+        assert!(parse_line(b"<34>1 - host su - - - caf\xff").is_none());
+    }
+
+    #[test]
+    fn parse_skips_lines_that_are_not_rfc5424() {
+        let input = "<34>1 - host su - - - one\nnot syslog\n<13>1 - - - - - - two\n";
+        let v = Rfc5424Parser.parse(input).unwrap();
+        assert_eq!(v.len(), 2);
+        assert_eq!(v[1].message.as_deref(), Some("two"));
+    }
+}
@@ -1,94 +1,84 @@
 use crate::config::LogEntry;
+use crate::parsers::multiline::Multiline;
+use crate::parsers::{LogParser, parse_chunked};
+use crate::severity::Severity;
 use anyhow::Result;
-use memchr::memchr_iter;
-
-#[cfg(feature = "parallel")]
-use rayon::prelude::*;
-
-const CHUNK_BYTES: usize = 4 * 1024 * 1024;
-const MIN_LINE_LEN: usize = 15;
+use std::sync::LazyLock;
 
 static SERVICE_SYSLOG: &str = "syslog";
-static LEVEL_INFO: &str = "info";
-static LEVEL_WARN: &str = "warn";
-static LEVEL_ERROR: &str = "error";
+/// Lines without a syslog header continue the previous message.
+static MULTILINE: LazyLock<Multiline> = LazyLock::new(|| Multiline::new().start_with(has_header));
+const LEVEL_INFO: Severity = Severity::Info;
+const LEVEL_WARN: Severity = Severity::Warn;
+const LEVEL_ERROR: Severity = Severity::Error;
 
 pub fn parse_syslog(input: &str) -> Result<Vec<LogEntry>> {
-    #[cfg(feature = "parallel")]
-    {
-        if input.len() > CHUNK_BYTES {
-            return Ok(parse_parallel(input));
-        }
+    Ok(parse_chunked(&SyslogParser, input.as_bytes()))
+}
+
+/// Registry adapter for [`parse_syslog`].
+pub struct SyslogParser;
+
+impl LogParser for SyslogParser {
+    fn name(&self) -> &str {
+        "syslog"
     }
 
-    Ok(parse_single(input.as_bytes()))
-}
+    fn aliases(&self) -> &[&str] {
+        &["system"]
+    }
 
-#[cfg(feature = "parallel")]
-fn parse_parallel(input: &str) -> Vec<LogEntry> {
-    let bytes = input.as_bytes();
-    let len = bytes.len();
-    let mut ranges = Vec::new();
-    let mut start = 0;
-
-    while start < len {
-        let mut end = (start + CHUNK_BYTES).min(len);
-        if end < len {
-            while end < len && bytes[end] != b'\n' {
-                end += 1;
-            }
-            if end < len {
-                end += 1;
-            }
-        } else {
-            end = len;
-        }
-        ranges.push(start..end);
-        start = end;
+    fn description(&self) -> &str {
+        "BSD syslog (RFC 3164) lines"
     }
 
-    let parts: Vec<Vec<LogEntry>> = ranges
-        .into_par_iter()
-        .map(|r| {
-            let mut out = Vec::with_capacity((r.end - r.start) / 80);
-            parse_chunk_into_vec(&bytes[r], &mut out);
-            out
-        })
-        .collect();
+    fn parse_line(&self, line: &[u8]) -> Option<LogEntry> {
+        parse_line(line)
+    }
+
+    fn multiline(&self) -> Option<&Multiline> {
+        Some(&MULTILINE)
+    }
 
-    let total: usize = parts.iter().map(Vec::len).sum();
-    let mut result = Vec::with_capacity(total);
-    for mut p in parts {
-        result.append(&mut p);
+    fn sniff(&self, line: &[u8]) -> bool {
+        has_header(line) && parse_line(line).is_some()
     }
-    result
 }
 
-fn parse_single(bytes: &[u8]) -> Vec<LogEntry> {
-    let mut out = Vec::with_capacity(bytes.len() / 80);
-    parse_chunk_into_vec(bytes, &mut out);
-    out
+/// An optional `<PRI>` followed by a BSD timestamp.
+fn has_header(line: &[u8]) -> bool {
+    let rest = match line.first() {
+        Some(b'<') => match memchr::memchr(b'>', line) {
+            Some(end) => &line[end + 1..],
+            None => return false,
+        },
+        _ => line,
+    };
+    is_bsd_timestamp(rest)
 }
 
-fn parse_chunk_into_vec(bytes: &[u8], out: &mut Vec<LogEntry>) {
-    let mut start = 0;
-    for nl in memchr_iter(b'\n', bytes) {
-        let line = &bytes[start..nl];
-        if line.len() >= MIN_LINE_LEN {
-            if let Some(entry) = parse_line(line) {
-                out.push(entry);
-            }
-        }
-        start = nl + 1;
-    }
-    if start < bytes.len() {
-        let line = &bytes[start..];
-        if line.len() >= MIN_LINE_LEN {
-            if let Some(entry) = parse_line(line) {
-                out.push(entry);
-            }
-        }
+/// Checks for a leading `Mmm dd hh:mm:ss ` timestamp (day may be space padded).
+pub(crate) fn is_bsd_timestamp(bytes: &[u8]) -> bool {
+    if bytes.len() < 16 {
+        return false;
     }
+    let b = &bytes[..16];
+    b[0].is_ascii_uppercase()
+        && b[1].is_ascii_lowercase()
+        && b[2].is_ascii_lowercase()
+        && b[3] == b' '
+        && (b[4] == b' ' || b[4].is_ascii_digit())
+        && b[5].is_ascii_digit()
+        && b[6] == b' '
+        && b[7].is_ascii_digit()
+        && b[8].is_ascii_digit()
+        && b[9] == b':'
+        && b[10].is_ascii_digit()
+        && b[11].is_ascii_digit()
+        && b[12] == b':'
+        && b[13].is_ascii_digit()
+        && b[14].is_ascii_digit()
+        && b[15] == b' '
 }
 
 fn parse_line(line: &[u8]) -> Option<LogEntry> {
@@ -96,17 +86,19 @@ fn parse_line(line: &[u8]) -> Option<LogEntry> {
     let len = bytes.len();
     let mut i = 0;
 
-    if line.get(0) == Some(&b'<') {
+    let mut pri = None;
+    if line.first() == Some(&b'<') {
         while i < len && bytes[i] != b'>' {
             i += 1;
         }
+        pri = parse_pri(&bytes[1..i.min(len)]);
         i += 1;
     }
 
     if i + 15 > len {
         return None;
     }
-    let timestamp = unsafe { std::str::from_utf8_unchecked(&bytes[i..i + 15]) };
+    let timestamp = std::str::from_utf8(&bytes[i..i + 15]).ok()?;
     i += 16;
 
     let host_start = i;
@@ -116,7 +108,7 @@ fn parse_line(line: &[u8]) -> Option<LogEntry> {
     if i >= len {
         return None;
     }
-    let hostname = unsafe { std::str::from_utf8_unchecked(&bytes[host_start..i]) };
+    let hostname = std::str::from_utf8(&bytes[host_start..i]).ok()?;
     i += 1;
 
     let app_start = i;
@@ -126,42 +118,170 @@ fn parse_line(line: &[u8]) -> Option<LogEntry> {
     if i >= len {
         return None;
     }
-    let app = unsafe { std::str::from_utf8_unchecked(&bytes[app_start..i]) };
+    let (app, pid) = split_tag(std::str::from_utf8(&bytes[app_start..i]).ok()?);
     i += 2;
 
     let message = if i < len {
-        unsafe { std::str::from_utf8_unchecked(&bytes[i..]) }
+        std::str::from_utf8(&bytes[i..]).ok()?
     } else {
         ""
     };
 
+    // PRI carries the exact severity; only guess from the text without it
+    let level = match pri {
+        Some(pri) => Severity::from_syslog(pri & 0x07).unwrap_or(LEVEL_INFO),
+        None => guess_level(message),
+    };
+
+    let app = if app.is_empty() { SERVICE_SYSLOG } else { app };
+    let mut entry = LogEntry {
+        timestamp: Some(timestamp.to_string()),
+        host: Some(hostname.to_string()),
+        service: Some(app.to_string()),
+        level: Some(level),
+        message: Some(message.to_string()),
+        ..Default::default()
+    };
+    if let Some(pid) = pid {
+        entry.set_field("pid", pid);
+    }
+    if let Some(facility) = pri.and_then(facility_name) {
+        entry.set_field("facility", facility);
+    }
+    Some(entry)
+}
+
+/// Split a `program[pid]` tag into the program name and its PID.
+pub(crate) fn split_tag(tag: &str) -> (&str, Option<u32>) {
+    if let Some(open) = tag.find('[')
+        && let Some(pid) = tag[open + 1..].strip_suffix(']')
+    {
+        return (&tag[..open], pid.parse().ok());
+    }
+    (tag, None)
+}
+
+/// Syslog facility names, indexed by facility code (`PRI >> 3`).
+const FACILITIES: [&str; 24] = [
+    "kern",
+    "user",
+    "mail",
+    "daemon",
+    "auth",
+    "syslog",
+    "lpr",
+    "news",
+    "uucp",
+    "cron",
+    "authpriv",
+    "ftp",
+    "ntp",
+    "security",
+    "console",
+    "solaris-cron",
+    "local0",
+    "local1",
+    "local2",
+    "local3",
+    "local4",
+    "local5",
+    "local6",
+    "local7",
+];
+
+/// Facility name for a decoded PRI value.
+pub(crate) fn facility_name(pri: u8) -> Option<&'static str> {
+    FACILITIES.get((pri >> 3) as usize).copied()
+}
+
+/// The 1-3 digits between `<` and `>`; valid PRI values are 0..=191.
+pub(crate) fn parse_pri(digits: &[u8]) -> Option<u8> {
+    if digits.is_empty() || digits.len() > 3 || !digits.iter().all(u8::is_ascii_digit) {
+        return None;
+    }
+    let value = digits
+        .iter()
+        .fold(0u16, |acc, d| acc * 10 + u16::from(d - b'0'));
+    u8::try_from(value).ok().filter(|v| *v <= 191)
+}
+
+/// Keyword heuristic for lines that carry no PRI.
+fn guess_level(message: &str) -> Severity {
     // Level detection without allocating new String
     let msg_bytes = message.as_bytes();
     let mut level_static = LEVEL_INFO;
     for b in msg_bytes {
         match *b {
-            b'E' | b'e' => {
+            b'E' | b'e'
                 if message.to_lowercase().contains("error")
-                    || message.to_lowercase().contains("fail")
-                {
-                    level_static = LEVEL_ERROR;
-                    break;
-                }
+                    || message.to_lowercase().contains("fail") =>
+            {
+                level_static = LEVEL_ERROR;
+                break;
             }
-            b'W' | b'w' => {
-                if message.to_lowercase().contains("warn") {
-                    level_static = LEVEL_WARN;
-                }
+            b'W' | b'w' if message.to_lowercase().contains("warn") => {
+                level_static = LEVEL_WARN;
             }
             _ => {}
         }
     }
+    level_static
+}
 
-    Some(LogEntry {
-        timestamp: Some(timestamp.to_string()),
-        host: Some(hostname.to_string()),
-        service: Some(SERVICE_SYSLOG.to_string()),
-        level: Some(level_static.to_string()),
-        message: Some(format!("{}: {}", app, message)),
-    })
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::FieldValue;
+
+    const SAMPLE: &str = r#"<34>Oct 11 22:14:15 mymachine su: 'su root' failed for lonvick on /dev/pts/8
+<165>Aug 24 05:34:00 host app: everything is fine
+Aug 24 05:34:00 host kernel: disk error on sda
+Aug 24 05:34:01 host sshd[1234]: Accepted publickey for root
+"#;
+
+    #[test]
+    fn parse_sample() {
+        let v = parse_syslog(SAMPLE).unwrap();
+        assert_eq!(v.len(), 4);
+        assert_eq!(v[0].service.as_deref(), Some("su"));
+        assert_eq!(
+            v[0].message.as_deref(),
+            Some("'su root' failed for lonvick on /dev/pts/8")
+        );
+        assert_eq!(v[0].level, Some(Severity::Critical));
+        assert_eq!(v[0].fields.get("facility"), Some(&FieldValue::from("auth")));
+        assert_eq!(v[1].level, Some(Severity::Notice));
+        assert_eq!(
+            v[1].fields.get("facility"),
+            Some(&FieldValue::from("local4"))
+        );
+        // no PRI: keyword heuristic, no facility
+        assert_eq!(v[2].level, Some(Severity::Error));
+        assert!(v[2].fields.is_empty());
+        assert_eq!(v[3].service.as_deref(), Some("sshd"));
+        assert_eq!(v[3].fields.get("pid"), Some(&FieldValue::Int(1234)));
+        assert_eq!(v[3].message.as_deref(), Some("Accepted publickey for root"));
+    }
+
+    #[test]
+    fn out_of_range_pri_is_ignored() {
+        assert_eq!(parse_pri(b"191"), Some(191));
+        assert_eq!(parse_pri(b"192"), None);
+        assert_eq!(parse_pri(b"1000"), None);
+        let entry = SyslogParser
+            .parse_line(b"<192>Aug 24 05:34:00 host app: disk error")
+            .unwrap();
+        assert_eq!(entry.level, Some(Severity::Error));
+        assert!(entry.fields.get("facility").is_none());
+    }
+
+    #[test]
+    fn parse_syslog_folds_lines_without_a_header() {
+        let input =
+            "<34>Oct 11 22:14:15 host su: first\n  more\nAug 24 05:34:00 host app: second\n";
+        let v = parse_syslog(input).unwrap();
+        assert_eq!(v.len(), 2);
+        assert_eq!(v[0].message.as_deref(), Some("first\n  more"));
+        assert_eq!(v[1].message.as_deref(), Some("second"));
+    }
 }
@@ -0,0 +1,154 @@
+use serde::{Deserialize, Deserializer, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Normalized log severity, ordered from least to most severe.
+///
+/// Serialized as the lowercase name; deserialized with [`FromStr`], so any
+/// spelling `--min-level` accepts is accepted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Trace,
+    Debug,
+    Info,
+    Notice,
+    Warn,
+    Error,
+    Critical,
+    Alert,
+    Emergency,
+}
+
+impl Severity {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Severity::Trace => "trace",
+            Severity::Debug => "debug",
+            Severity::Info => "info",
+            Severity::Notice => "notice",
+            Severity::Warn => "warn",
+            Severity::Error => "error",
+            Severity::Critical => "critical",
+            Severity::Alert => "alert",
+            Severity::Emergency => "emergency",
+        }
+    }
+
+    /// Syslog severity (the low 3 bits of PRI, RFC 5424 table 2).
+    pub fn from_syslog(severity: u8) -> Option<Self> {
+        Some(match severity {
+            0 => Severity::Emergency,
+            1 => Severity::Alert,
+            2 => Severity::Critical,
+            3 => Severity::Error,
+            4 => Severity::Warn,
+            5 => Severity::Notice,
+            6 => Severity::Info,
+            7 => Severity::Debug,
+            _ => return None,
+        })
+    }
+
+    /// journald `PRIORITY=` field, which uses the syslog severity numbers.
+    pub fn from_journald_priority(priority: &str) -> Option<Self> {
+        priority.trim().parse().ok().and_then(Self::from_syslog)
+    }
+
+    /// Python `logging` level names (`NOTSET` maps to trace).
+    pub fn from_python(level: &str) -> Option<Self> {
+        Some(match level {
+            "NOTSET" => Severity::Trace,
+            "DEBUG" => Severity::Debug,
+            "INFO" => Severity::Info,
+            "WARNING" | "WARN" => Severity::Warn,
+            "ERROR" => Severity::Error,
+            "CRITICAL" | "FATAL" => Severity::Critical,
+            _ => return None,
+        })
+    }
+
+    /// Status class of an HTTP response: 5xx error, 4xx warn, else info.
+    pub fn from_http_status(status: u16) -> Self {
+        match status {
+            500.. => Severity::Error,
+            400..=499 => Severity::Warn,
+            _ => Severity::Info,
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Severity {
+    type Err = String;
+
+    /// Case-insensitive level names and their common spellings
+    /// (`warning`, `err`, `crit`, `fatal`, `emerg`, ...).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.to_ascii_lowercase().as_str() {
+            "trace" | "verbose" => Severity::Trace,
+            "debug" | "dbg" => Severity::Debug,
+            "info" | "information" | "informational" => Severity::Info,
+            "notice" => Severity::Notice,
+            "warn" | "warning" => Severity::Warn,
+            "error" | "err" => Severity::Error,
+            "critical" | "crit" | "fatal" => Severity::Critical,
+            "alert" => Severity::Alert,
+            "emergency" | "emerg" | "panic" => Severity::Emergency,
+            _ => return Err(format!("unknown severity '{}'", s)),
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for Severity {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        name.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn orders_by_severity() {
+        assert!(Severity::Debug < Severity::Info && Severity::Error < Severity::Emergency);
+    }
+
+    #[test]
+    fn maps_source_levels() {
+        assert_eq!(Severity::from_syslog(3), Some(Severity::Error));
+        assert_eq!(Severity::from_syslog(8), None);
+        assert_eq!(
+            Severity::from_journald_priority("5"),
+            Some(Severity::Notice)
+        );
+        assert_eq!(Severity::from_python("CRITICAL"), Some(Severity::Critical));
+        assert_eq!(Severity::from_http_status(404), Severity::Warn);
+    }
+
+    #[test]
+    fn parses_and_deserializes_the_same_spellings() {
+        for (name, level) in [
+            ("WARNING", Severity::Warn),
+            ("crit", Severity::Critical),
+            ("verbose", Severity::Trace),
+            ("information", Severity::Info),
+            ("dbg", Severity::Debug),
+            ("panic", Severity::Emergency),
+        ] {
+            assert_eq!(name.parse(), Ok(level));
+            let json = format!("\"{}\"", name);
+            assert_eq!(serde_json::from_str::<Severity>(&json).unwrap(), level);
+        }
+        assert!("loud".parse::<Severity>().is_err());
+        assert!(serde_json::from_str::<Severity>(r#""loud""#).is_err());
+        assert_eq!(serde_json::to_string(&Severity::Warn).unwrap(), r#""warn""#);
+    }
+}